@@ -0,0 +1,125 @@
+// 用户角色与状态转换的权限检查
+use crate::history::Action;
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Role {
+    Author,
+    Reviewer,
+    Editor,
+    Admin,
+}
+
+impl Role {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Role::Author => "author",
+            Role::Reviewer => "reviewer",
+            Role::Editor => "editor",
+            Role::Admin => "admin",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Role> {
+        match s {
+            "author" => Some(Role::Author),
+            "reviewer" => Some(Role::Reviewer),
+            "editor" => Some(Role::Editor),
+            "admin" => Some(Role::Admin),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct User {
+    pub name: String,
+    pub role: Role,
+}
+
+impl User {
+    pub fn new(name: &str, role: Role) -> User {
+        User {
+            name: String::from(name),
+            role,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TransitionError {
+    // 当前状态不允许该操作
+    InvalidTransition { action: Action, state: &'static str },
+    // 角色没有权限
+    Forbidden { action: Action, role: Role },
+    // 作者审批自己的文章
+    SelfApproval,
+}
+
+impl fmt::Display for TransitionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TransitionError::InvalidTransition { action, state } => {
+                write!(f, "cannot {} a post in state {}", action.as_str(), state)
+            }
+            TransitionError::Forbidden { action, role } => {
+                write!(f, "role {} may not {}", role.as_str(), action.as_str())
+            }
+            TransitionError::SelfApproval => write!(f, "authors may not approve their own posts"),
+        }
+    }
+}
+
+impl std::error::Error for TransitionError {}
+
+// 作者只能编辑和提交自己的文章，审批类操作必须由其他人完成
+pub fn check(action: Action, actor: &User, author: &str) -> Result<(), TransitionError> {
+    let is_author = actor.name == author;
+    let allowed = match action {
+        Action::Create => true,
+        Action::AddText | Action::RequestReview => {
+            is_author || matches!(actor.role, Role::Editor | Role::Admin)
+        }
+        Action::Approve | Action::Schedule | Action::Reject => actor.role != Role::Author,
+        Action::Unpublish => matches!(actor.role, Role::Editor | Role::Admin),
+        // 定时发布由调度器完成，不需要用户操作
+        Action::Publish => false,
+    };
+    if !allowed {
+        return Err(TransitionError::Forbidden {
+            action,
+            role: actor.role,
+        });
+    }
+    if is_author && matches!(action, Action::Approve | Action::Schedule) {
+        return Err(TransitionError::SelfApproval);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn authors_cannot_approve() {
+        let alice = User::new("alice", Role::Author);
+        assert!(check(Action::AddText, &alice, "alice").is_ok());
+        assert_eq!(
+            Err(TransitionError::Forbidden {
+                action: Action::AddText,
+                role: Role::Author
+            }),
+            check(Action::AddText, &alice, "bob")
+        );
+        assert!(check(Action::Approve, &alice, "bob").is_err());
+
+        // 即使是管理员也不能审批自己的文章
+        let root = User::new("root", Role::Admin);
+        assert_eq!(
+            Err(TransitionError::SelfApproval),
+            check(Action::Approve, &root, "root")
+        );
+        assert!(check(Action::Approve, &root, "alice").is_ok());
+    }
+}
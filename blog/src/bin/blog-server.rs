@@ -0,0 +1,31 @@
+use blog::server;
+use blog::storage::{FileRepository, MemoryRepository};
+use std::env;
+use std::net::TcpListener;
+use std::process;
+
+// 用法：blog-server [数据目录]，不指定目录时文章只保存在内存中
+// 监听地址可以通过 BLOG_ADDR 环境变量修改
+fn main() {
+    let addr = env::var("BLOG_ADDR").unwrap_or_else(|_| String::from("127.0.0.1:7878"));
+    let listener = TcpListener::bind(&addr).unwrap_or_else(|err| {
+        eprintln!("Problem binding {}: {}", addr, err);
+        process::exit(1);
+    });
+    println!("Listening on http://{}", addr);
+
+    let result = match env::args().nth(1) {
+        Some(dir) => {
+            let mut repo = FileRepository::open(dir).unwrap_or_else(|err| {
+                eprintln!("Problem opening repository: {}", err);
+                process::exit(1);
+            });
+            server::serve(listener, &mut repo)
+        }
+        None => server::serve(listener, &mut MemoryRepository::new()),
+    };
+    if let Err(e) = result {
+        eprintln!("Server error: {}", e);
+        process::exit(1);
+    }
+}
@@ -0,0 +1,417 @@
+// 已发布文章的评论，支持回复形成讨论串
+// 审核状态使用状态对象（State 模式）实现：待审核、已通过、垃圾评论
+use crate::auth::{Role, User};
+use crate::schedule::{Clock, SystemClock};
+use crate::storage::PostRepository;
+use crate::Stage;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt;
+use std::sync::Arc;
+
+trait ModerationState {
+    fn approve(self: Box<Self>) -> Box<dyn ModerationState>;
+    fn mark_spam(self: Box<Self>) -> Box<dyn ModerationState>;
+    // 默认不展示内容，只有审核通过的评论才返回文本
+    fn body<'a>(&self, _comment: &'a Comment) -> &'a str {
+        ""
+    }
+    fn name(&self) -> &'static str;
+}
+
+// 待审核
+struct Pending {}
+
+impl ModerationState for Pending {
+    fn approve(self: Box<Self>) -> Box<dyn ModerationState> {
+        Box::new(Approved {})
+    }
+    fn mark_spam(self: Box<Self>) -> Box<dyn ModerationState> {
+        Box::new(Spam {})
+    }
+    fn name(&self) -> &'static str {
+        "pending"
+    }
+}
+
+// 审核通过
+struct Approved {}
+
+impl ModerationState for Approved {
+    fn approve(self: Box<Self>) -> Box<dyn ModerationState> {
+        self
+    }
+    fn mark_spam(self: Box<Self>) -> Box<dyn ModerationState> {
+        Box::new(Spam {})
+    }
+    fn body<'a>(&self, comment: &'a Comment) -> &'a str {
+        &comment.body
+    }
+    fn name(&self) -> &'static str {
+        "approved"
+    }
+}
+
+// 垃圾评论，误判时仍可以重新通过
+struct Spam {}
+
+impl ModerationState for Spam {
+    fn approve(self: Box<Self>) -> Box<dyn ModerationState> {
+        Box::new(Approved {})
+    }
+    fn mark_spam(self: Box<Self>) -> Box<dyn ModerationState> {
+        self
+    }
+    fn name(&self) -> &'static str {
+        "spam"
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CommentError {
+    // 只能评论已发布的文章
+    PostNotPublished,
+    PostNotFound(u64),
+    // 读取文章失败，保存错误信息
+    Storage(String),
+    NotFound(u64),
+    // 回复的评论不存在或不属于同一篇文章
+    InvalidParent(u64),
+    // 只有审核者、编辑和管理员可以审核评论
+    Forbidden,
+    InvalidTransition { state: &'static str },
+}
+
+impl fmt::Display for CommentError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CommentError::PostNotPublished => write!(f, "comments require a published post"),
+            CommentError::PostNotFound(id) => write!(f, "post {} not found", id),
+            CommentError::Storage(msg) => write!(f, "cannot load post: {}", msg),
+            CommentError::NotFound(id) => write!(f, "comment {} not found", id),
+            CommentError::InvalidParent(id) => write!(f, "cannot reply to comment {}", id),
+            CommentError::Forbidden => write!(f, "only moderators may moderate comments"),
+            CommentError::InvalidTransition { state } => {
+                write!(f, "comment is already {}", state)
+            }
+        }
+    }
+}
+
+impl std::error::Error for CommentError {}
+
+pub struct Comment {
+    id: u64,
+    post_id: u64,
+    parent: Option<u64>,
+    author: String,
+    body: String,
+    created_at: u64,
+    state: Option<Box<dyn ModerationState>>,
+}
+
+impl Comment {
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+    pub fn post_id(&self) -> u64 {
+        self.post_id
+    }
+    pub fn parent(&self) -> Option<u64> {
+        self.parent
+    }
+    pub fn author(&self) -> &str {
+        &self.author
+    }
+    pub fn created_at(&self) -> u64 {
+        self.created_at
+    }
+    pub fn body(&self) -> &str {
+        match &self.state {
+            Some(s) => s.body(self),
+            None => "",
+        }
+    }
+    pub fn state_name(&self) -> &'static str {
+        match &self.state {
+            Some(s) => s.name(),
+            None => "",
+        }
+    }
+
+    fn transition(
+        &mut self,
+        f: impl FnOnce(Box<dyn ModerationState>) -> Box<dyn ModerationState>,
+    ) -> Result<(), CommentError> {
+        if let Some(s) = self.state.take() {
+            let before = s.name();
+            let next = f(s);
+            let changed = next.name() != before;
+            self.state = Some(next);
+            if changed {
+                return Ok(());
+            }
+        }
+        Err(CommentError::InvalidTransition {
+            state: self.state_name(),
+        })
+    }
+}
+
+fn check_moderator(user: &User) -> Result<(), CommentError> {
+    match user.role {
+        Role::Author => Err(CommentError::Forbidden),
+        _ => Ok(()),
+    }
+}
+
+pub struct CommentBoard {
+    comments: BTreeMap<u64, Comment>,
+    next_id: u64,
+    // 评论时间的来源，和文章一样可以注入
+    clock: Arc<dyn Clock>,
+}
+
+impl Default for CommentBoard {
+    fn default() -> Self {
+        CommentBoard::with_clock(Arc::new(SystemClock))
+    }
+}
+
+impl CommentBoard {
+    pub fn new() -> CommentBoard {
+        CommentBoard::default()
+    }
+
+    // 和文章共用同一个时钟时，评论时间与审计记录一致
+    pub fn with_clock(clock: Arc<dyn Clock>) -> CommentBoard {
+        CommentBoard {
+            comments: BTreeMap::new(),
+            next_id: 0,
+            clock,
+        }
+    }
+
+    // 新评论进入待审核状态，parent 为回复的评论 id；文章从仓库中按 id 读取
+    pub fn add<R: PostRepository>(
+        &mut self,
+        repo: &R,
+        post_id: u64,
+        author: &User,
+        parent: Option<u64>,
+        body: &str,
+    ) -> Result<u64, CommentError> {
+        let post = repo
+            .get(post_id)
+            .map_err(|e| CommentError::Storage(e.to_string()))?
+            .ok_or(CommentError::PostNotFound(post_id))?;
+        if post.stage() != Stage::Published {
+            return Err(CommentError::PostNotPublished);
+        }
+        if let Some(parent) = parent {
+            match self.comments.get(&parent) {
+                Some(c) if c.post_id == post_id => {}
+                _ => return Err(CommentError::InvalidParent(parent)),
+            }
+        }
+        self.next_id += 1;
+        let comment = Comment {
+            id: self.next_id,
+            post_id,
+            parent,
+            author: author.name.clone(),
+            body: String::from(body),
+            created_at: self.clock.now(),
+            state: Some(Box::new(Pending {})),
+        };
+        self.comments.insert(self.next_id, comment);
+        Ok(self.next_id)
+    }
+
+    pub fn get(&self, id: u64) -> Option<&Comment> {
+        self.comments.get(&id)
+    }
+
+    pub fn approve(&mut self, id: u64, moderator: &User) -> Result<(), CommentError> {
+        check_moderator(moderator)?;
+        self.get_mut(id)?.transition(|s| s.approve())
+    }
+
+    pub fn mark_spam(&mut self, id: u64, moderator: &User) -> Result<(), CommentError> {
+        check_moderator(moderator)?;
+        self.get_mut(id)?.transition(|s| s.mark_spam())
+    }
+
+    fn get_mut(&mut self, id: u64) -> Result<&mut Comment, CommentError> {
+        self.comments.get_mut(&id).ok_or(CommentError::NotFound(id))
+    }
+
+    // 审核队列：按提交顺序排列的待审核评论
+    pub fn moderation_queue(&self) -> Vec<&Comment> {
+        self.comments
+            .values()
+            .filter(|c| c.state_name() == "pending")
+            .collect()
+    }
+
+    // 文章下显示出来的评论数，与 thread 返回的条数一致
+    pub fn count(&self, post_id: u64) -> usize {
+        self.thread(post_id).len()
+    }
+
+    // 没有可显示评论的文章不出现在结果中
+    pub fn counts(&self) -> HashMap<u64, usize> {
+        let post_ids: BTreeSet<u64> = self.comments.values().map(|c| c.post_id).collect();
+        post_ids
+            .into_iter()
+            .map(|id| (id, self.count(id)))
+            .filter(|(_, n)| *n > 0)
+            .collect()
+    }
+
+    // 按讨论串顺序返回 (缩进层级, 评论)，只包含审核通过的评论；
+    // 父评论未通过时，它下面的回复也不显示
+    pub fn thread(&self, post_id: u64) -> Vec<(usize, &Comment)> {
+        let mut children: HashMap<Option<u64>, Vec<&Comment>> = HashMap::new();
+        for c in self.comments.values() {
+            if c.post_id == post_id && c.state_name() == "approved" {
+                children.entry(c.parent).or_default().push(c);
+            }
+        }
+        let mut out = Vec::new();
+        let mut stack: Vec<(usize, &Comment)> = children
+            .get(&None)
+            .map(|roots| roots.iter().rev().map(|c| (0, *c)).collect())
+            .unwrap_or_default();
+        while let Some((depth, c)) = stack.pop() {
+            out.push((depth, c));
+            if let Some(replies) = children.get(&Some(c.id)) {
+                stack.extend(replies.iter().rev().map(|r| (depth + 1, *r)));
+            }
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::schedule::ManualClock;
+    use crate::storage::MemoryRepository;
+    use crate::Post;
+
+    fn published_post() -> Post {
+        let alice = User::new("alice", Role::Author);
+        let mut post = Post::new(&alice);
+        post.add_text(&alice, "hello").unwrap();
+        post.request_review(&alice).unwrap();
+        post.approve(&User::new("bob", Role::Reviewer)).unwrap();
+        post
+    }
+
+    // id 为 1 和 2 的两篇已发布文章
+    fn repo() -> MemoryRepository {
+        let mut repo = MemoryRepository::new();
+        repo.insert(&published_post()).unwrap();
+        repo.insert(&published_post()).unwrap();
+        repo
+    }
+
+    #[test]
+    fn comments_need_published_post() {
+        let reader = User::new("reader", Role::Author);
+        let mut board = CommentBoard::new();
+        let mut repo = repo();
+        let draft = repo.insert(&Post::new(&reader)).unwrap();
+        assert_eq!(
+            Err(CommentError::PostNotPublished),
+            board.add(&repo, draft, &reader, None, "hi")
+        );
+        assert_eq!(
+            Err(CommentError::PostNotFound(9)),
+            board.add(&repo, 9, &reader, None, "hi")
+        );
+        assert_eq!(
+            Err(CommentError::InvalidParent(7)),
+            board.add(&repo, 1, &reader, Some(7), "hi")
+        );
+        let first = board.add(&repo, 1, &reader, None, "hi").unwrap();
+        assert_eq!(
+            Err(CommentError::InvalidParent(first)),
+            board.add(&repo, 2, &reader, Some(first), "hi")
+        );
+    }
+
+    #[test]
+    fn comments_use_the_clock() {
+        let clock = Arc::new(ManualClock::new(50));
+        let mut board = CommentBoard::with_clock(clock.clone());
+        let repo = repo();
+        let reader = User::new("reader", Role::Author);
+        let first = board.add(&repo, 1, &reader, None, "hi").unwrap();
+        clock.advance(7);
+        let second = board.add(&repo, 1, &reader, None, "again").unwrap();
+        assert_eq!(50, board.get(first).unwrap().created_at());
+        assert_eq!(57, board.get(second).unwrap().created_at());
+    }
+
+    #[test]
+    fn moderation_flow() {
+        let reader = User::new("reader", Role::Author);
+        let moderator = User::new("mod", Role::Reviewer);
+        let repo = repo();
+        let mut board = CommentBoard::new();
+
+        let id = board.add(&repo, 1, &reader, None, "first!").unwrap();
+        assert_eq!("", board.get(id).unwrap().body());
+        assert_eq!(1, board.moderation_queue().len());
+        assert_eq!(Err(CommentError::Forbidden), board.approve(id, &reader));
+
+        board.approve(id, &moderator).unwrap();
+        assert_eq!("first!", board.get(id).unwrap().body());
+        assert!(board.approve(id, &moderator).is_err());
+        assert!(board.moderation_queue().is_empty());
+        assert_eq!(1, board.count(1));
+
+        board.mark_spam(id, &moderator).unwrap();
+        assert_eq!("spam", board.get(id).unwrap().state_name());
+        assert_eq!(0, board.count(1));
+        assert_eq!(
+            Err(CommentError::NotFound(9)),
+            board.mark_spam(9, &moderator)
+        );
+    }
+
+    #[test]
+    fn threads_nest_replies() {
+        let reader = User::new("reader", Role::Author);
+        let moderator = User::new("mod", Role::Editor);
+        let repo = repo();
+        let mut board = CommentBoard::new();
+
+        let a = board.add(&repo, 1, &reader, None, "a").unwrap();
+        let b = board.add(&repo, 1, &reader, None, "b").unwrap();
+        let a1 = board.add(&repo, 1, &reader, Some(a), "a1").unwrap();
+        let a1x = board.add(&repo, 1, &reader, Some(a1), "a1x").unwrap();
+        let b1 = board.add(&repo, 1, &reader, Some(b), "b1").unwrap();
+        let other = board.add(&repo, 2, &reader, None, "other").unwrap();
+        for id in [a, b, a1, a1x, b1, other] {
+            board.approve(id, &moderator).unwrap();
+        }
+        board.mark_spam(b, &moderator).unwrap();
+
+        let thread: Vec<(usize, &str)> = board
+            .thread(1)
+            .into_iter()
+            .map(|(depth, c)| (depth, c.body()))
+            .collect();
+        assert_eq!(vec![(0, "a"), (1, "a1"), (2, "a1x")], thread);
+        // b 被标记为垃圾评论后，b1 虽然通过了审核也不显示，不计入评论数
+        assert_eq!(3, board.count(1));
+        assert_eq!(Some(&3), board.counts().get(&1));
+        assert_eq!(Some(&1), board.counts().get(&2));
+
+        board.mark_spam(other, &moderator).unwrap();
+        assert_eq!(None, board.counts().get(&2));
+    }
+}
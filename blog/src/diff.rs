@@ -0,0 +1,133 @@
+// 基于最长公共子序列的按行 diff，输出 unified 格式
+const CONTEXT: usize = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Keep,
+    Delete,
+    Insert,
+}
+
+// 返回 (操作, 旧行号, 新行号) 序列
+fn edit_script(a: &[&str], b: &[&str]) -> Vec<(Op, usize, usize)> {
+    let (n, m) = (a.len(), b.len());
+    // lcs[i][j] 为 a[i..] 与 b[j..] 的最长公共子序列长度
+    let mut lcs = vec![vec![0usize; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lcs[i][j] = if a[i] == b[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut ops = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < n || j < m {
+        if i < n && j < m && a[i] == b[j] {
+            ops.push((Op::Keep, i, j));
+            i += 1;
+            j += 1;
+        } else if i < n && (j == m || lcs[i + 1][j] >= lcs[i][j + 1]) {
+            // 删除优先于插入，输出中 "-" 行排在 "+" 行之前
+            ops.push((Op::Delete, i, j));
+            i += 1;
+        } else {
+            ops.push((Op::Insert, i, j));
+            j += 1;
+        }
+    }
+    ops
+}
+
+fn range(start: usize, len: usize) -> String {
+    // unified 格式中空范围的起始行号指向前一行
+    let start = if len == 0 { start } else { start + 1 };
+    if len == 1 {
+        format!("{}", start)
+    } else {
+        format!("{},{}", start, len)
+    }
+}
+
+pub fn unified(old: &str, new: &str, old_name: &str, new_name: &str) -> String {
+    let a: Vec<&str> = old.lines().collect();
+    let b: Vec<&str> = new.lines().collect();
+    let ops = edit_script(&a, &b);
+
+    let mut out = String::new();
+    let changes: Vec<usize> = (0..ops.len()).filter(|&k| ops[k].0 != Op::Keep).collect();
+    if changes.is_empty() {
+        return out;
+    }
+    out.push_str(&format!("--- {}\n+++ {}\n", old_name, new_name));
+
+    // 相距不超过两倍上下文的修改合并为同一个 hunk
+    let mut k = 0;
+    while k < changes.len() {
+        let first = changes[k];
+        let mut last = first;
+        while k + 1 < changes.len() && changes[k + 1] - last <= 2 * CONTEXT {
+            k += 1;
+            last = changes[k];
+        }
+        k += 1;
+
+        let start = first.saturating_sub(CONTEXT);
+        let end = (last + CONTEXT + 1).min(ops.len());
+        let hunk = &ops[start..end];
+        let old_len = hunk.iter().filter(|op| op.0 != Op::Insert).count();
+        let new_len = hunk.iter().filter(|op| op.0 != Op::Delete).count();
+        out.push_str(&format!(
+            "@@ -{} +{} @@\n",
+            range(hunk[0].1, old_len),
+            range(hunk[0].2, new_len)
+        ));
+        for &(op, i, j) in hunk {
+            match op {
+                Op::Keep => out.push_str(&format!(" {}\n", a[i])),
+                Op::Delete => out.push_str(&format!("-{}\n", a[i])),
+                Op::Insert => out.push_str(&format!("+{}\n", b[j])),
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn identical_text_has_no_diff() {
+        assert_eq!("", unified("a\nb", "a\nb", "a", "b"));
+    }
+
+    #[test]
+    fn single_line_change() {
+        let diff = unified("one\ntwo\nthree", "one\n2\nthree", "rev0", "rev1");
+        assert_eq!(
+            "--- rev0\n+++ rev1\n@@ -1,3 +1,3 @@\n one\n-two\n+2\n three\n",
+            diff
+        );
+    }
+
+    #[test]
+    fn insert_into_empty() {
+        let diff = unified("", "hello", "rev0", "rev1");
+        assert_eq!("--- rev0\n+++ rev1\n@@ -0,0 +1 @@\n+hello\n", diff);
+    }
+
+    #[test]
+    fn distant_changes_split_into_hunks() {
+        let old: Vec<String> = (1..=20).map(|i| i.to_string()).collect();
+        let mut new = old.clone();
+        new[1] = String::from("two");
+        new[17] = String::from("eighteen");
+        let diff = unified(&old.join("\n"), &new.join("\n"), "a", "b");
+        let headers: Vec<&str> = diff.lines().filter(|l| l.starts_with("@@")).collect();
+        assert_eq!(vec!["@@ -1,5 +1,5 @@", "@@ -15,6 +15,6 @@"], headers);
+    }
+}
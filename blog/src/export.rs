@@ -0,0 +1,479 @@
+// 把仓库中已发布的文章导出为静态网站：文章页、首页、标签页以及 RSS 2.0 和 Atom 订阅
+// 页面之间使用相对链接，可以直接从本地目录打开；订阅需要站点的绝对地址
+use crate::render::{escape_html, heading_level, render_post};
+use crate::storage::{PostRepository, StoreError};
+use crate::Post;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::Path;
+
+pub struct SiteConfig {
+    pub title: String,
+    // 站点根地址，例如 https://example.com/blog ，不带结尾的 '/'
+    base_url: Option<String>,
+}
+
+impl SiteConfig {
+    pub fn new(title: &str) -> SiteConfig {
+        SiteConfig {
+            title: String::from(title),
+            base_url: None,
+        }
+    }
+
+    // 设置根地址后才生成订阅，订阅中的链接必须是绝对地址
+    pub fn with_base_url(mut self, url: &str) -> Result<SiteConfig, &'static str> {
+        let url = url.trim().trim_end_matches('/');
+        let host = url
+            .strip_prefix("https://")
+            .or_else(|| url.strip_prefix("http://"));
+        match host {
+            Some(host) if !host.is_empty() => {
+                self.base_url = Some(String::from(url));
+                Ok(self)
+            }
+            _ => Err("base url must be an absolute http(s) url"),
+        }
+    }
+
+    pub fn base_url(&self) -> Option<&str> {
+        self.base_url.as_deref()
+    }
+}
+
+// 导出时使用的文章信息
+struct Entry {
+    id: u64,
+    title: String,
+    author: String,
+    tags: Vec<String>,
+    published_at: u64,
+    html: String,
+}
+
+// 第一个标题作为文章标题，没有标题时使用第一行
+fn title_of(id: u64, post: &Post) -> String {
+    let content = post.content();
+    let heading = content
+        .lines()
+        .filter_map(heading_level)
+        .map(|(_, text)| text)
+        .find(|text| !text.is_empty());
+    let first = content.lines().map(|l| l.trim()).find(|l| !l.is_empty());
+    match heading.or(first) {
+        Some(t) if !t.is_empty() => String::from(t),
+        _ => format!("Post {}", id),
+    }
+}
+
+// 标签转成文件名安全的形式，只有标点的标签使用 "tag"；
+// 不同的标签可能得到相同的结果，导出时再用 tag_slugs 去重
+pub fn slug(tag: &str) -> String {
+    let mut out = String::new();
+    for c in tag.trim().to_lowercase().chars() {
+        if c.is_alphanumeric() {
+            out.push(c);
+        } else if !out.ends_with('-') {
+            out.push('-');
+        }
+    }
+    let out = out.trim_matches('-');
+    if out.is_empty() {
+        String::from("tag")
+    } else {
+        String::from(out)
+    }
+}
+
+// 为每个标签分配不重复的文件名，冲突时按标签名的顺序加上 -2、-3 等后缀
+fn tag_slugs<'a>(tags: impl Iterator<Item = &'a String>) -> BTreeMap<&'a str, String> {
+    let names: BTreeSet<&str> = tags.map(|t| t.as_str()).collect();
+    let mut used = BTreeSet::new();
+    let mut slugs = BTreeMap::new();
+    for name in names {
+        let base = slug(name);
+        let mut candidate = base.clone();
+        let mut n = 2;
+        while used.contains(&candidate) {
+            candidate = format!("{}-{}", base, n);
+            n += 1;
+        }
+        used.insert(candidate.clone());
+        slugs.insert(name, candidate);
+    }
+    slugs
+}
+
+// Unix 天数转公历日期，返回 (年, 月, 日)
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+fn split_time(ts: u64) -> (i64, u32, u32, u64, u64, u64) {
+    let days = (ts / 86_400) as i64;
+    let secs = ts % 86_400;
+    let (y, m, d) = civil_from_days(days);
+    (y, m, d, secs / 3600, secs / 60 % 60, secs % 60)
+}
+
+// RSS 使用的 RFC 822 时间
+pub fn rfc822(ts: u64) -> String {
+    const DAYS: [&str; 7] = ["Thu", "Fri", "Sat", "Sun", "Mon", "Tue", "Wed"];
+    const MONTHS: [&str; 12] = [
+        "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+    ];
+    let (y, m, d, hh, mm, ss) = split_time(ts);
+    format!(
+        "{}, {:02} {} {} {:02}:{:02}:{:02} +0000",
+        DAYS[(ts / 86_400 % 7) as usize],
+        d,
+        MONTHS[m as usize - 1],
+        y,
+        hh,
+        mm,
+        ss
+    )
+}
+
+// Atom 使用的 RFC 3339 时间
+pub fn rfc3339(ts: u64) -> String {
+    let (y, m, d, hh, mm, ss) = split_time(ts);
+    format!("{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z", y, m, d, hh, mm, ss)
+}
+
+// root 是从当前页面回到站点根目录的相对路径，首页为 ""，子目录中的页面为 "../"
+fn page(site: &SiteConfig, root: &str, title: &str, body: &str) -> String {
+    let feeds = if site.base_url.is_some() {
+        format!(
+            "<link rel=\"alternate\" type=\"application/rss+xml\" href=\"{}rss.xml\">\n\
+             <link rel=\"alternate\" type=\"application/atom+xml\" href=\"{}atom.xml\">\n",
+            root, root
+        )
+    } else {
+        String::new()
+    };
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n{}\
+         </head>\n<body>\n<header><a href=\"{}index.html\">{}</a></header>\n{}</body>\n</html>\n",
+        escape_html(title),
+        feeds,
+        root,
+        escape_html(&site.title),
+        body
+    )
+}
+
+// 订阅中使用的绝对地址
+fn post_url(base_url: &str, id: u64) -> String {
+    format!("{}/posts/{}.html", base_url, id)
+}
+
+fn entry_list(root: &str, entries: &[&Entry]) -> String {
+    let mut out = String::from("<ul>\n");
+    for e in entries {
+        out.push_str(&format!(
+            "<li><a href=\"{}posts/{}.html\">{}</a> <time datetime=\"{}\">{}</time></li>\n",
+            root,
+            e.id,
+            escape_html(&e.title),
+            rfc3339(e.published_at),
+            &rfc3339(e.published_at)[..10]
+        ));
+    }
+    out.push_str("</ul>\n");
+    out
+}
+
+fn post_page(site: &SiteConfig, slugs: &BTreeMap<&str, String>, e: &Entry) -> String {
+    let mut body = format!(
+        "<article>\n<p class=\"meta\">{} · <time datetime=\"{}\">{}</time></p>\n{}",
+        escape_html(&e.author),
+        rfc3339(e.published_at),
+        &rfc3339(e.published_at)[..10],
+        e.html
+    );
+    if !e.tags.is_empty() {
+        body.push_str("<p class=\"tags\">");
+        let links: Vec<String> = e
+            .tags
+            .iter()
+            .map(|t| {
+                format!(
+                    "<a href=\"../tags/{}.html\">{}</a>",
+                    slugs[t.as_str()],
+                    escape_html(t)
+                )
+            })
+            .collect();
+        body.push_str(&links.join(" "));
+        body.push_str("</p>\n");
+    }
+    body.push_str("</article>\n");
+    page(site, "../", &e.title, &body)
+}
+
+fn rss(site: &SiteConfig, base_url: &str, entries: &[Entry]) -> String {
+    let mut out = String::from(
+        "<?xml version=\"1.0\" encoding=\"utf-8\"?>\n<rss version=\"2.0\">\n<channel>\n",
+    );
+    out.push_str(&format!(
+        "<title>{}</title>\n<link>{}/index.html</link>\n<description>{}</description>\n",
+        escape_html(&site.title),
+        escape_html(base_url),
+        escape_html(&site.title)
+    ));
+    if let Some(latest) = entries.first() {
+        out.push_str(&format!(
+            "<lastBuildDate>{}</lastBuildDate>\n",
+            rfc822(latest.published_at)
+        ));
+    }
+    for e in entries {
+        let url = escape_html(&post_url(base_url, e.id));
+        out.push_str(&format!(
+            "<item>\n<title>{}</title>\n<link>{}</link>\n<guid>{}</guid>\n<pubDate>{}</pubDate>\n",
+            escape_html(&e.title),
+            url,
+            url,
+            rfc822(e.published_at)
+        ));
+        for tag in &e.tags {
+            out.push_str(&format!("<category>{}</category>\n", escape_html(tag)));
+        }
+        out.push_str(&format!(
+            "<description>{}</description>\n</item>\n",
+            escape_html(&e.html)
+        ));
+    }
+    out.push_str("</channel>\n</rss>\n");
+    out
+}
+
+fn atom(site: &SiteConfig, base_url: &str, entries: &[Entry]) -> String {
+    let mut out = String::from(
+        "<?xml version=\"1.0\" encoding=\"utf-8\"?>\n<feed xmlns=\"http://www.w3.org/2005/Atom\">\n",
+    );
+    let updated = entries.first().map_or(0, |e| e.published_at);
+    out.push_str(&format!(
+        "<title>{}</title>\n<id>{}/</id>\n<link href=\"{}/index.html\"/>\n\
+         <link rel=\"self\" href=\"{}/atom.xml\"/>\n<updated>{}</updated>\n",
+        escape_html(&site.title),
+        escape_html(base_url),
+        escape_html(base_url),
+        escape_html(base_url),
+        rfc3339(updated)
+    ));
+    for e in entries {
+        let url = escape_html(&post_url(base_url, e.id));
+        out.push_str(&format!(
+            "<entry>\n<title>{}</title>\n<id>{}</id>\n<link href=\"{}\"/>\n<updated>{}</updated>\n\
+             <author><name>{}</name></author>\n",
+            escape_html(&e.title),
+            url,
+            url,
+            rfc3339(e.published_at),
+            escape_html(&e.author)
+        ));
+        for tag in &e.tags {
+            out.push_str(&format!("<category term=\"{}\"/>\n", escape_html(tag)));
+        }
+        out.push_str(&format!(
+            "<content type=\"html\">{}</content>\n</entry>\n",
+            escape_html(&e.html)
+        ));
+    }
+    out.push_str("</feed>\n");
+    out
+}
+
+// 导出所有已发布文章，返回导出的文章数；没有设置根地址时不生成订阅
+pub fn export<R: PostRepository>(
+    repo: &R,
+    out: &Path,
+    site: &SiteConfig,
+) -> Result<usize, StoreError> {
+    let mut entries = Vec::new();
+    for id in repo.ids()? {
+        if let Some(post) = repo.get(id)? {
+            if let Some(published_at) = post.published_at() {
+                entries.push(Entry {
+                    id,
+                    title: title_of(id, &post),
+                    author: String::from(post.author()),
+                    tags: post.tags().to_vec(),
+                    published_at,
+                    html: render_post(&post),
+                });
+            }
+        }
+    }
+    // 最新的文章排在前面
+    entries.sort_by(|a, b| b.published_at.cmp(&a.published_at).then(b.id.cmp(&a.id)));
+
+    fs::create_dir_all(out.join("posts"))?;
+    fs::create_dir_all(out.join("tags"))?;
+
+    let slugs = tag_slugs(entries.iter().flat_map(|e| &e.tags));
+    let mut tags: BTreeMap<&str, Vec<&Entry>> = BTreeMap::new();
+    for e in &entries {
+        fs::write(
+            out.join(format!("posts/{}.html", e.id)),
+            post_page(site, &slugs, e),
+        )?;
+        for tag in &e.tags {
+            tags.entry(tag).or_default().push(e);
+        }
+    }
+
+    let all: Vec<&Entry> = entries.iter().collect();
+    let mut index = format!("<h1>{}</h1>\n", escape_html(&site.title));
+    index.push_str(&entry_list("", &all));
+    if !tags.is_empty() {
+        index.push_str("<h2>Tags</h2>\n<ul>\n");
+        for (name, items) in &tags {
+            index.push_str(&format!(
+                "<li><a href=\"tags/{}.html\">{}</a> ({})</li>\n",
+                slugs[name],
+                escape_html(name),
+                items.len()
+            ));
+        }
+        index.push_str("</ul>\n");
+    }
+    fs::write(out.join("index.html"), page(site, "", &site.title, &index))?;
+
+    for (name, items) in &tags {
+        let body = format!(
+            "<h1>Tag: {}</h1>\n{}",
+            escape_html(name),
+            entry_list("../", items)
+        );
+        fs::write(
+            out.join(format!("tags/{}.html", slugs[name])),
+            page(site, "../", &format!("{} - {}", name, site.title), &body),
+        )?;
+    }
+
+    if let Some(base_url) = site.base_url() {
+        fs::write(out.join("rss.xml"), rss(site, base_url, &entries))?;
+        fs::write(out.join("atom.xml"), atom(site, base_url, &entries))?;
+    }
+    Ok(entries.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::{Role, User};
+    use crate::storage::MemoryRepository;
+
+    #[test]
+    fn formats_dates() {
+        assert_eq!("Thu, 01 Jan 1970 00:00:00 +0000", rfc822(0));
+        assert_eq!("Tue, 14 Nov 2023 22:13:20 +0000", rfc822(1_700_000_000));
+        assert_eq!("2023-11-14T22:13:20Z", rfc3339(1_700_000_000));
+        assert_eq!("2000-02-29T00:00:00Z", rfc3339(951_782_400));
+    }
+
+    #[test]
+    fn slugs() {
+        assert_eq!("rust-lang", slug(" Rust Lang! "));
+        assert_eq!("c", slug("C++"));
+        assert_eq!("tag", slug("++"));
+
+        let tags: Vec<String> = ["C++", "c-2", "C", "++"]
+            .iter()
+            .map(|t| String::from(*t))
+            .collect();
+        let slugs = tag_slugs(tags.iter());
+        assert_eq!("tag", slugs["++"]);
+        assert_eq!("c", slugs["C"]);
+        assert_eq!("c-2", slugs["C++"]);
+        assert_eq!("c-2-2", slugs["c-2"]);
+    }
+
+    #[test]
+    fn titles_need_heading_syntax() {
+        let alice = User::new("alice", Role::Author);
+        let bob = User::new("bob", Role::Reviewer);
+        let mut post = Post::new(&alice);
+        post.add_text(&alice, "#hashtag first\n\n## Real title")
+            .unwrap();
+        post.request_review(&alice).unwrap();
+        post.approve(&bob).unwrap();
+        assert_eq!("Real title", title_of(1, &post));
+    }
+
+    #[test]
+    fn exports_published_posts() {
+        let alice = User::new("alice", Role::Author);
+        let bob = User::new("bob", Role::Reviewer);
+        let mut repo = MemoryRepository::new();
+
+        let mut post = Post::new(&alice);
+        post.add_text(&alice, "# Hello <World>\n\nbody").unwrap();
+        post.add_tag("Rust Lang");
+        post.request_review(&alice).unwrap();
+        post.approve(&bob).unwrap();
+        let published = repo.insert(&post).unwrap();
+        repo.insert(&Post::new(&alice)).unwrap();
+
+        let dir = std::env::temp_dir().join(format!("blog-export-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let site = SiteConfig::new("My Blog")
+            .with_base_url("https://example.com/")
+            .unwrap();
+        assert_eq!(1, export(&repo, &dir, &site).unwrap());
+
+        // 页面之间是相对链接
+        let page = fs::read_to_string(dir.join(format!("posts/{}.html", published))).unwrap();
+        assert!(page.contains("<title>Hello &lt;World&gt;</title>"));
+        assert!(page.contains("<h1>Hello &lt;World&gt;</h1>"));
+        assert!(page.contains("href=\"../tags/rust-lang.html\""));
+        assert!(page.contains("href=\"../index.html\""));
+        assert!(page.contains("href=\"../rss.xml\""));
+
+        let index = fs::read_to_string(dir.join("index.html")).unwrap();
+        assert!(index.contains("href=\"posts/1.html\""));
+        assert!(index.contains("href=\"tags/rust-lang.html\""));
+        assert!(!index.contains("posts/2.html"));
+
+        let tag = fs::read_to_string(dir.join("tags/rust-lang.html")).unwrap();
+        assert!(tag.contains("Tag: Rust Lang"));
+        assert!(tag.contains("href=\"../posts/1.html\""));
+
+        let rss = fs::read_to_string(dir.join("rss.xml")).unwrap();
+        assert!(rss.contains("<guid>https://example.com/posts/1.html</guid>"));
+        assert!(rss.contains("&lt;h1&gt;Hello &amp;lt;World&amp;gt;&lt;/h1&gt;"));
+        let atom = fs::read_to_string(dir.join("atom.xml")).unwrap();
+        assert!(atom.contains("<author><name>alice</name></author>"));
+        assert!(atom.contains("<id>https://example.com/posts/1.html</id>"));
+        fs::remove_dir_all(&dir).unwrap();
+
+        // 没有根地址时只生成页面
+        assert_eq!(1, export(&repo, &dir, &SiteConfig::new("My Blog")).unwrap());
+        assert!(!dir.join("rss.xml").exists());
+        let index = fs::read_to_string(dir.join("index.html")).unwrap();
+        assert!(!index.contains("rss.xml"));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn base_url_must_be_absolute() {
+        assert!(SiteConfig::new("x").with_base_url(".").is_err());
+        assert!(SiteConfig::new("x").with_base_url("https://").is_err());
+        let site = SiteConfig::new("x")
+            .with_base_url("http://localhost:8000/blog/")
+            .unwrap();
+        assert_eq!(Some("http://localhost:8000/blog"), site.base_url());
+    }
+}
@@ -0,0 +1,61 @@
+// 文章的审计记录：每次状态转换或编辑都保存时间、操作者和内容快照
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Create,
+    AddText,
+    RequestReview,
+    Approve,
+    Reject,
+    Schedule,
+    Publish,
+    Unpublish,
+}
+
+impl Action {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Action::Create => "create",
+            Action::AddText => "add_text",
+            Action::RequestReview => "request_review",
+            Action::Approve => "approve",
+            Action::Reject => "reject",
+            Action::Schedule => "schedule",
+            Action::Publish => "publish",
+            Action::Unpublish => "unpublish",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Action> {
+        match s {
+            "create" => Some(Action::Create),
+            "add_text" => Some(Action::AddText),
+            "request_review" => Some(Action::RequestReview),
+            "approve" => Some(Action::Approve),
+            "reject" => Some(Action::Reject),
+            "schedule" => Some(Action::Schedule),
+            "publish" => Some(Action::Publish),
+            "unpublish" => Some(Action::Unpublish),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Revision {
+    // 秒级 Unix 时间戳
+    pub timestamp: u64,
+    pub actor: String,
+    pub action: Action,
+    // 操作完成后的状态名称
+    pub state: String,
+    pub content: String,
+}
+
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
@@ -1,70 +1,410 @@
-trait State {
-    fn request_review(self: Box<Self>) -> Box<dyn State>;
-    fn approve(self: Box<Self>) -> Box<dyn State>;
+pub mod auth;
+pub mod comments;
+pub mod diff;
+pub mod export;
+pub mod history;
+pub mod machine;
+pub mod render;
+pub mod schedule;
+pub mod search;
+pub mod server;
+pub mod storage;
+pub mod typed;
+
+use auth::{TransitionError, User};
+use history::{Action, Revision};
+use machine::Machine;
+use schedule::{Clock, SystemClock};
+use std::sync::{Arc, OnceLock};
+
+// 文章所处的阶段
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Stage {
+    // 草稿
+    Draft,
+    // 等待审批
+    PendingReview,
+    // 定时发布
+    Scheduled,
+    // 出版
+    Published,
 }
-// 草稿
-struct Draft {}
 
-impl State for Draft {
-    fn request_review(self: Box<Self>) -> Box<dyn State> {
-        Box::new(PendingReview {})
+impl Stage {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Stage::Draft => "draft",
+            Stage::PendingReview => "pending_review",
+            Stage::Scheduled => "scheduled",
+            Stage::Published => "published",
+        }
     }
-    fn approve(self: Box<Self>) -> Box<dyn State> {
-        self
+
+    pub fn parse(s: &str) -> Option<Stage> {
+        match s {
+            "draft" => Some(Stage::Draft),
+            "pending_review" => Some(Stage::PendingReview),
+            "scheduled" => Some(Stage::Scheduled),
+            "published" => Some(Stage::Published),
+            _ => None,
+        }
     }
 }
 
-// 等待审批
-struct PendingReview {}
-
-impl State for PendingReview {
-    fn request_review(self: Box<Self>) -> Box<dyn State> {
-        self
-    }
-    fn approve(self: Box<Self>) -> Box<dyn State> {
-        Box::new(Published {})
-    }
+// 状态机守卫和动作使用的上下文
+struct Timing {
+    now: u64,
+    publish_at: Option<u64>,
 }
 
-// 出版
-struct Published {}
+// 文章工作流的转换表，事件直接使用审计记录中的操作
+fn workflow() -> &'static Machine<Stage, Action, Timing> {
+    static WORKFLOW: OnceLock<Machine<Stage, Action, Timing>> = OnceLock::new();
+    WORKFLOW.get_or_init(|| {
+        use Action::*;
+        use Stage::*;
+        Machine::builder(Draft)
+            .states(&[PendingReview, Scheduled, Published])
+            // 只有草稿可以编辑，编辑是草稿上的内部转换
+            .transition(Draft, AddText, Draft)
+            .transition(Draft, RequestReview, PendingReview)
+            .transition(PendingReview, Approve, Published)
+            // 退回草稿，作者可以继续修改
+            .transition(PendingReview, Reject, Draft)
+            .guarded(
+                PendingReview,
+                Schedule,
+                Scheduled,
+                "has_publish_time",
+                |t: &Timing| t.publish_at.is_some(),
+            )
+            .guarded(Scheduled, Publish, Published, "due", |t: &Timing| {
+                t.publish_at.is_some_and(|p| t.now >= p)
+            })
+            .transition(Scheduled, Unpublish, Draft)
+            .transition(Published, Unpublish, Draft)
+            // 离开定时发布后不再保留发布时间
+            .on_exit(Scheduled, |t: &mut Timing| t.publish_at = None)
+            .build()
+            .expect("blog workflow is valid")
+    })
+}
 
-impl State for Published {
-    fn request_review(self: Box<Self>) -> Box<dyn State> {
-        self
-    }
-    fn approve(self: Box<Self>) -> Box<dyn State> {
-        self
-    }
+// 文章工作流的 Graphviz DOT 图
+pub fn workflow_dot() -> String {
+    workflow().to_dot("post")
 }
 
 pub struct Post {
-    state: Option<Box<dyn State>>,
+    stage: Stage,
+    // 定时发布的时间，只在 Scheduled 阶段有值
+    publish_at: Option<u64>,
     content: String,
+    history: Vec<Revision>,
+    author: String,
+    tags: Vec<String>,
+    // 审计记录和状态转换使用的时间来源
+    clock: Arc<dyn Clock>,
 }
 
 impl Post {
-    pub fn new() -> Post {
+    pub fn new(author: &User) -> Post {
+        Post::with_clock(author, Arc::new(SystemClock))
+    }
+    // 使用指定的时钟，测试中可以控制记录的时间
+    pub fn with_clock(author: &User, clock: Arc<dyn Clock>) -> Post {
+        let mut post = Post::with_stage(Stage::Draft, None, String::new());
+        post.clock = clock;
+        post.author = author.name.clone();
+        post.record(Action::Create, &author.name);
+        post
+    }
+    fn with_stage(stage: Stage, publish_at: Option<u64>, content: String) -> Post {
         Post {
-            state: Some(Box::new(Draft {})),
-            content: String::new(),
+            stage,
+            publish_at,
+            content,
+            history: Vec::new(),
+            author: String::new(),
+            tags: Vec::new(),
+            clock: Arc::new(SystemClock),
         }
     }
-    pub fn add_text(&mut self, text: &str) {
-        self.content.push_str(text)
+    // 从存储加载的文章默认使用系统时钟
+    pub fn set_clock(&mut self, clock: Arc<dyn Clock>) {
+        self.clock = clock;
+    }
+    pub fn author(&self) -> &str {
+        &self.author
+    }
+    // 标签只是分类信息，不影响状态流转
+    pub fn add_tag(&mut self, tag: &str) {
+        let tag = tag.trim();
+        if !tag.is_empty() && !self.tags.iter().any(|t| t == tag) {
+            self.tags.push(String::from(tag));
+        }
+    }
+    pub fn tags(&self) -> &[String] {
+        &self.tags
+    }
+    // 只有草稿状态可以追加文本
+    pub fn add_text(&mut self, actor: &User, text: &str) -> Result<(), TransitionError> {
+        auth::check(Action::AddText, actor, &self.author)?;
+        self.fire(Action::AddText, self.clock.now(), self.publish_at)?;
+        self.content.push_str(text);
+        self.record(Action::AddText, &actor.name);
+        Ok(())
     }
+    // 只有出版状态才返回文本
     pub fn content(&self) -> &str {
-        ""
+        match self.stage {
+            Stage::Published => &self.content,
+            _ => "",
+        }
+    }
+    pub fn stage(&self) -> Stage {
+        self.stage
+    }
+    pub fn state_name(&self) -> &'static str {
+        self.stage.as_str()
+    }
+    pub fn request_review(&mut self, actor: &User) -> Result<(), TransitionError> {
+        self.transition(Action::RequestReview, actor, self.publish_at)
+    }
+
+    pub fn approve(&mut self, actor: &User) -> Result<(), TransitionError> {
+        self.transition(Action::Approve, actor, self.publish_at)
+    }
+
+    pub fn reject(&mut self, actor: &User) -> Result<(), TransitionError> {
+        self.transition(Action::Reject, actor, self.publish_at)
+    }
+
+    // 审批通过，到 publish_at（Unix 秒）时再发布
+    pub fn schedule(&mut self, actor: &User, publish_at: u64) -> Result<(), TransitionError> {
+        self.transition(Action::Schedule, actor, Some(publish_at))
+    }
+
+    // 推进时间，到期的定时文章转为出版；由调度器调用，未到期时什么也不做
+    pub fn tick(&mut self, now: u64) {
+        if self.fire(Action::Publish, now, self.publish_at).is_ok() {
+            self.record_at(Action::Publish, "scheduler", now);
+        }
+    }
+
+    // 已发布或定时发布的文章撤回到草稿
+    pub fn unpublish(&mut self, actor: &User) -> Result<(), TransitionError> {
+        self.transition(Action::Unpublish, actor, self.publish_at)
+    }
+
+    pub fn publish_at(&self) -> Option<u64> {
+        self.publish_at
     }
-    pub fn request_review(&mut self) {
-        if let Some(s) = self.state.take() {
-            self.state = Some(s.request_review())
+
+    // 最近一次进入出版状态的时间
+    pub fn published_at(&self) -> Option<u64> {
+        if self.stage != Stage::Published {
+            return None;
         }
+        self.history
+            .iter()
+            .rev()
+            .find(|r| r.state == "published")
+            .map(|r| r.timestamp)
+    }
+
+    // 所有修订记录，下标即修订号
+    pub fn history(&self) -> &[Revision] {
+        &self.history
+    }
+
+    // 两个修订之间内容的 unified diff，修订号不存在时返回 None
+    pub fn diff(&self, rev_a: usize, rev_b: usize) -> Option<String> {
+        let a = self.history.get(rev_a)?;
+        let b = self.history.get(rev_b)?;
+        Some(diff::unified(
+            &a.content,
+            &b.content,
+            &format!("rev{}", rev_a),
+            &format!("rev{}", rev_b),
+        ))
     }
 
-    pub fn approve(&mut self) {
-        if let Some(s) = self.state.take() {
-            self.state = Some(s.approve())
+    fn transition(
+        &mut self,
+        action: Action,
+        actor: &User,
+        publish_at: Option<u64>,
+    ) -> Result<(), TransitionError> {
+        auth::check(action, actor, &self.author)?;
+        let now = self.clock.now();
+        self.fire(action, now, publish_at)?;
+        self.record_at(action, &actor.name, now);
+        Ok(())
+    }
+
+    // 通过状态机执行转换，当前阶段不接受该操作时返回错误
+    fn fire(
+        &mut self,
+        action: Action,
+        now: u64,
+        publish_at: Option<u64>,
+    ) -> Result<(), TransitionError> {
+        let mut timing = Timing { now, publish_at };
+        match workflow().fire(self.stage, action, &mut timing) {
+            Ok(next) => {
+                self.stage = next;
+                self.publish_at = timing.publish_at;
+                Ok(())
+            }
+            Err(_) => Err(TransitionError::InvalidTransition {
+                action,
+                state: self.state_name(),
+            }),
         }
     }
+
+    fn record(&mut self, action: Action, actor: &str) {
+        self.record_at(action, actor, self.clock.now());
+    }
+
+    fn record_at(&mut self, action: Action, actor: &str, timestamp: u64) {
+        self.history.push(Revision {
+            timestamp,
+            actor: String::from(actor),
+            action,
+            state: String::from(self.state_name()),
+            content: self.content.clone(),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use auth::Role;
+
+    fn alice() -> User {
+        User::new("alice", Role::Author)
+    }
+
+    fn bob() -> User {
+        User::new("bob", Role::Reviewer)
+    }
+
+    #[test]
+    fn content_only_visible_after_publish() {
+        let mut post = Post::new(&alice());
+        post.add_text(&alice(), "I ate a salad for lunch today")
+            .unwrap();
+        assert_eq!("", post.content());
+
+        post.request_review(&alice()).unwrap();
+        assert_eq!("", post.content());
+
+        post.approve(&bob()).unwrap();
+        assert_eq!("I ate a salad for lunch today", post.content());
+    }
+
+    #[test]
+    fn add_text_rejected_outside_draft() {
+        let mut post = Post::new(&alice());
+        post.add_text(&alice(), "draft").unwrap();
+        post.request_review(&alice()).unwrap();
+        assert_eq!(
+            Err(TransitionError::InvalidTransition {
+                action: Action::AddText,
+                state: "pending_review"
+            }),
+            post.add_text(&alice(), " pending")
+        );
+        post.approve(&bob()).unwrap();
+        assert!(post.add_text(&alice(), " published").is_err());
+        assert_eq!("draft", post.content());
+    }
+
+    #[test]
+    fn invalid_transitions_are_errors() {
+        let mut post = Post::new(&alice());
+        assert!(post.approve(&bob()).is_err());
+        post.request_review(&alice()).unwrap();
+        post.approve(&bob()).unwrap();
+        assert_eq!(
+            Err(TransitionError::InvalidTransition {
+                action: Action::Approve,
+                state: "published"
+            }),
+            post.approve(&bob())
+        );
+    }
+
+    #[test]
+    fn roles_are_enforced() {
+        let mut post = Post::new(&alice());
+        post.request_review(&alice()).unwrap();
+        // 作者不能审批，包括自己的文章
+        assert_eq!(
+            Err(TransitionError::Forbidden {
+                action: Action::Approve,
+                role: Role::Author
+            }),
+            post.approve(&alice())
+        );
+        let editor = User::new("alice", Role::Editor);
+        assert_eq!(Err(TransitionError::SelfApproval), post.approve(&editor));
+        post.approve(&bob()).unwrap();
+        // 审核者不能撤回已发布的文章
+        assert!(post.unpublish(&bob()).is_err());
+        post.unpublish(&User::new("carol", Role::Editor)).unwrap();
+        assert_eq!("draft", post.state_name());
+    }
+
+    #[test]
+    fn reject_returns_to_draft() {
+        let mut post = Post::new(&alice());
+        post.add_text(&alice(), "first").unwrap();
+        post.request_review(&alice()).unwrap();
+        post.reject(&bob()).unwrap();
+        assert_eq!("draft", post.state_name());
+        post.add_text(&alice(), " second").unwrap();
+        post.request_review(&alice()).unwrap();
+        post.approve(&bob()).unwrap();
+        assert_eq!("first second", post.content());
+    }
+
+    #[test]
+    fn history_records_transitions() {
+        let mut post = Post::new(&alice());
+        post.add_text(&alice(), "hello\n").unwrap();
+        post.request_review(&alice()).unwrap();
+        // 非法转换不会记录
+        assert!(post.request_review(&alice()).is_err());
+        post.reject(&bob()).unwrap();
+        post.add_text(&alice(), "world\n").unwrap();
+
+        let actions: Vec<Action> = post.history().iter().map(|r| r.action).collect();
+        assert_eq!(
+            vec![
+                Action::Create,
+                Action::AddText,
+                Action::RequestReview,
+                Action::Reject,
+                Action::AddText
+            ],
+            actions
+        );
+        assert_eq!("bob", post.history()[3].actor);
+        assert_eq!("draft", post.history()[3].state);
+
+        let diff = post.diff(1, 4).unwrap();
+        assert_eq!("--- rev1\n+++ rev4\n@@ -1 +1,2 @@\n hello\n+world\n", diff);
+        assert!(post.diff(0, 9).is_none());
+    }
+
+    #[test]
+    fn workflow_graph() {
+        let dot = workflow_dot();
+        assert!(dot.contains("\"Draft\" [shape=doublecircle]"));
+        assert!(dot.contains("\"Scheduled\" -> \"Published\" [label=\"Publish [due]\"]"));
+    }
 }
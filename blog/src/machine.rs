@@ -0,0 +1,395 @@
+// 通用状态机：状态、事件、守卫条件、进入/退出动作、转换表校验以及 Graphviz DOT 导出
+// S 为状态，E 为事件，C 为守卫和动作使用的上下文
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt;
+use std::hash::Hash;
+
+type Guard<C> = Box<dyn Fn(&C) -> bool + Send + Sync>;
+type Action<C> = Box<dyn Fn(&mut C) + Send + Sync>;
+
+struct Transition<S, E, C> {
+    from: S,
+    event: E,
+    to: S,
+    guard: Option<(String, Guard<C>)>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MachineError<S, E> {
+    // 当前状态没有该事件的转换
+    NoTransition { state: S, event: E },
+    // 有对应的转换，但守卫条件都不满足
+    GuardRejected { state: S, event: E },
+}
+
+impl<S: fmt::Debug, E: fmt::Debug> fmt::Display for MachineError<S, E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MachineError::NoTransition { state, event } => {
+                write!(f, "no transition for {:?} in state {:?}", event, state)
+            }
+            MachineError::GuardRejected { state, event } => {
+                write!(f, "guard rejected {:?} in state {:?}", event, state)
+            }
+        }
+    }
+}
+
+impl<S: fmt::Debug, E: fmt::Debug> std::error::Error for MachineError<S, E> {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ValidationError<S, E> {
+    // 转换中使用了没有声明的状态
+    UnknownState(S),
+    // 从初始状态无法到达
+    Unreachable(S),
+    // 同一状态同一事件有多个无守卫的转换，结果不确定
+    Ambiguous { state: S, event: E },
+}
+
+impl<S: fmt::Debug, E: fmt::Debug> fmt::Display for ValidationError<S, E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ValidationError::UnknownState(s) => write!(f, "undeclared state {:?}", s),
+            ValidationError::Unreachable(s) => write!(f, "state {:?} is unreachable", s),
+            ValidationError::Ambiguous { state, event } => {
+                write!(
+                    f,
+                    "ambiguous transitions for {:?} in state {:?}",
+                    event, state
+                )
+            }
+        }
+    }
+}
+
+impl<S: fmt::Debug, E: fmt::Debug> std::error::Error for ValidationError<S, E> {}
+
+pub struct Builder<S, E, C> {
+    initial: S,
+    states: Vec<S>,
+    transitions: Vec<Transition<S, E, C>>,
+    on_entry: HashMap<S, Vec<Action<C>>>,
+    on_exit: HashMap<S, Vec<Action<C>>>,
+}
+
+impl<S, E, C> Builder<S, E, C>
+where
+    S: Copy + Eq + Hash + fmt::Debug,
+    E: Copy + Eq + fmt::Debug,
+{
+    pub fn states(mut self, states: &[S]) -> Self {
+        for s in states {
+            if !self.states.contains(s) {
+                self.states.push(*s);
+            }
+        }
+        self
+    }
+
+    pub fn transition(mut self, from: S, event: E, to: S) -> Self {
+        self.transitions.push(Transition {
+            from,
+            event,
+            to,
+            guard: None,
+        });
+        self
+    }
+
+    // 带守卫的转换，name 用于错误信息和 DOT 导出
+    pub fn guarded(
+        mut self,
+        from: S,
+        event: E,
+        to: S,
+        name: &str,
+        guard: impl Fn(&C) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        self.transitions.push(Transition {
+            from,
+            event,
+            to,
+            guard: Some((String::from(name), Box::new(guard))),
+        });
+        self
+    }
+
+    pub fn on_entry(mut self, state: S, action: impl Fn(&mut C) + Send + Sync + 'static) -> Self {
+        self.on_entry
+            .entry(state)
+            .or_default()
+            .push(Box::new(action));
+        self
+    }
+
+    pub fn on_exit(mut self, state: S, action: impl Fn(&mut C) + Send + Sync + 'static) -> Self {
+        self.on_exit
+            .entry(state)
+            .or_default()
+            .push(Box::new(action));
+        self
+    }
+
+    // 校验转换表，返回发现的全部问题
+    pub fn build(self) -> Result<Machine<S, E, C>, Vec<ValidationError<S, E>>> {
+        let mut errors = Vec::new();
+        let declared: HashSet<S> = self.states.iter().copied().collect();
+        let mut referenced = vec![self.initial];
+        for t in &self.transitions {
+            referenced.push(t.from);
+            referenced.push(t.to);
+        }
+        referenced.extend(self.on_entry.keys().copied());
+        referenced.extend(self.on_exit.keys().copied());
+        for s in referenced {
+            let error = ValidationError::UnknownState(s);
+            if !declared.contains(&s) && !errors.contains(&error) {
+                errors.push(error);
+            }
+        }
+
+        for (i, t) in self.transitions.iter().enumerate() {
+            if t.guard.is_some() {
+                continue;
+            }
+            let duplicate = self.transitions[..i]
+                .iter()
+                .any(|o| o.guard.is_none() && o.from == t.from && o.event == t.event);
+            let error = ValidationError::Ambiguous {
+                state: t.from,
+                event: t.event,
+            };
+            if duplicate && !errors.contains(&error) {
+                errors.push(error);
+            }
+        }
+
+        let mut reachable = HashSet::new();
+        let mut queue = VecDeque::from([self.initial]);
+        while let Some(s) = queue.pop_front() {
+            if reachable.insert(s) {
+                queue.extend(
+                    self.transitions
+                        .iter()
+                        .filter(|t| t.from == s)
+                        .map(|t| t.to),
+                );
+            }
+        }
+        for s in &self.states {
+            if !reachable.contains(s) {
+                errors.push(ValidationError::Unreachable(*s));
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(Machine {
+                initial: self.initial,
+                states: self.states,
+                transitions: self.transitions,
+                on_entry: self.on_entry,
+                on_exit: self.on_exit,
+            })
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+pub struct Machine<S, E, C> {
+    initial: S,
+    states: Vec<S>,
+    transitions: Vec<Transition<S, E, C>>,
+    on_entry: HashMap<S, Vec<Action<C>>>,
+    on_exit: HashMap<S, Vec<Action<C>>>,
+}
+
+impl<S, E, C> Machine<S, E, C>
+where
+    S: Copy + Eq + Hash + fmt::Debug,
+    E: Copy + Eq + fmt::Debug,
+{
+    pub fn builder(initial: S) -> Builder<S, E, C> {
+        Builder {
+            initial,
+            states: vec![initial],
+            transitions: Vec::new(),
+            on_entry: HashMap::new(),
+            on_exit: HashMap::new(),
+        }
+    }
+
+    pub fn initial(&self) -> S {
+        self.initial
+    }
+
+    pub fn states(&self) -> &[S] {
+        &self.states
+    }
+
+    // 当前状态下是否有该事件的转换（不考虑守卫）
+    pub fn accepts(&self, state: S, event: E) -> bool {
+        self.transitions
+            .iter()
+            .any(|t| t.from == state && t.event == event)
+    }
+
+    // 按声明顺序选择第一个守卫通过的转换并执行退出、进入动作；
+    // 自身转换视为内部转换，不执行进入和退出动作
+    pub fn fire(&self, state: S, event: E, ctx: &mut C) -> Result<S, MachineError<S, E>> {
+        let mut candidates = self
+            .transitions
+            .iter()
+            .filter(|t| t.from == state && t.event == event)
+            .peekable();
+        if candidates.peek().is_none() {
+            return Err(MachineError::NoTransition { state, event });
+        }
+        let chosen = candidates.find(|t| match &t.guard {
+            Some((_, guard)) => guard(ctx),
+            None => true,
+        });
+        let t = match chosen {
+            Some(t) => t,
+            None => return Err(MachineError::GuardRejected { state, event }),
+        };
+        if t.to != state {
+            for action in self.on_exit.get(&state).into_iter().flatten() {
+                action(ctx);
+            }
+            for action in self.on_entry.get(&t.to).into_iter().flatten() {
+                action(ctx);
+            }
+        }
+        Ok(t.to)
+    }
+
+    // 导出 Graphviz DOT 格式，初始状态用双圈表示，守卫名写在边的标签中
+    pub fn to_dot(&self, name: &str) -> String {
+        let mut out = format!("digraph {} {{\n    rankdir=LR;\n", name);
+        for s in &self.states {
+            let shape = if *s == self.initial {
+                "doublecircle"
+            } else {
+                "circle"
+            };
+            out.push_str(&format!("    \"{:?}\" [shape={}];\n", s, shape));
+        }
+        for t in &self.transitions {
+            let label = match &t.guard {
+                Some((guard, _)) => format!("{:?} [{}]", t.event, guard),
+                None => format!("{:?}", t.event),
+            };
+            out.push_str(&format!(
+                "    \"{:?}\" -> \"{:?}\" [label=\"{}\"];\n",
+                t.from, t.to, label
+            ));
+        }
+        out.push_str("}\n");
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    enum Door {
+        Open,
+        Closed,
+        Locked,
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    enum Input {
+        Open,
+        Close,
+        Lock,
+        Unlock,
+    }
+
+    #[derive(Default)]
+    struct Ctx {
+        has_key: bool,
+        log: Vec<&'static str>,
+    }
+
+    fn door() -> Machine<Door, Input, Ctx> {
+        Machine::builder(Door::Closed)
+            .states(&[Door::Open, Door::Locked])
+            .transition(Door::Closed, Input::Open, Door::Open)
+            .transition(Door::Open, Input::Close, Door::Closed)
+            .transition(Door::Closed, Input::Lock, Door::Locked)
+            .guarded(
+                Door::Locked,
+                Input::Unlock,
+                Door::Closed,
+                "has_key",
+                |c: &Ctx| c.has_key,
+            )
+            .on_exit(Door::Locked, |c: &mut Ctx| c.log.push("exit locked"))
+            .on_entry(Door::Closed, |c: &mut Ctx| c.log.push("enter closed"))
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn fires_transitions_with_guards_and_actions() {
+        let machine = door();
+        let mut ctx = Ctx::default();
+        let state = machine.fire(Door::Closed, Input::Lock, &mut ctx).unwrap();
+        assert_eq!(Door::Locked, state);
+        assert_eq!(
+            Err(MachineError::GuardRejected {
+                state: Door::Locked,
+                event: Input::Unlock
+            }),
+            machine.fire(state, Input::Unlock, &mut ctx)
+        );
+        ctx.has_key = true;
+        assert_eq!(
+            Ok(Door::Closed),
+            machine.fire(state, Input::Unlock, &mut ctx)
+        );
+        assert_eq!(vec!["exit locked", "enter closed"], ctx.log);
+        assert_eq!(
+            Err(MachineError::NoTransition {
+                state: Door::Closed,
+                event: Input::Close
+            }),
+            machine.fire(Door::Closed, Input::Close, &mut ctx)
+        );
+    }
+
+    #[test]
+    fn validation_reports_problems() {
+        let errors = Machine::<Door, Input, Ctx>::builder(Door::Closed)
+            .states(&[Door::Locked])
+            .transition(Door::Closed, Input::Open, Door::Open)
+            .transition(Door::Closed, Input::Open, Door::Closed)
+            .build()
+            .err()
+            .unwrap();
+        assert_eq!(
+            vec![
+                ValidationError::UnknownState(Door::Open),
+                ValidationError::Ambiguous {
+                    state: Door::Closed,
+                    event: Input::Open
+                },
+                ValidationError::Unreachable(Door::Locked),
+            ],
+            errors
+        );
+    }
+
+    #[test]
+    fn exports_dot() {
+        let dot = door().to_dot("door");
+        assert!(dot.starts_with("digraph door {\n"));
+        assert!(dot.contains("    \"Closed\" [shape=doublecircle];\n"));
+        assert!(dot.contains("    \"Locked\" -> \"Closed\" [label=\"Unlock [has_key]\"];\n"));
+    }
+}
@@ -1,3 +1,95 @@
+use blog::auth::{Role, User};
+use blog::export::{self, SiteConfig};
+use blog::storage::FileRepository;
+use blog::Post;
+use std::env;
+use std::path::Path;
+use std::process;
+
+struct ExportConfig {
+    repo_dir: String,
+    out_dir: String,
+    site: SiteConfig,
+}
+
+impl ExportConfig {
+    // blog export <仓库目录> <输出目录> [--title 标题] [--base-url 地址]
+    // 只有指定了绝对的 --base-url 才生成 RSS 和 Atom 订阅
+    fn new(args: &[String]) -> Result<ExportConfig, &'static str> {
+        if args.len() < 4 {
+            return Err("usage: blog export <repo-dir> <out-dir> [--title T] [--base-url URL]");
+        }
+        let mut site = SiteConfig::new("Blog");
+        let mut rest = args[4..].iter();
+        while let Some(flag) = rest.next() {
+            let value = match rest.next() {
+                Some(v) => v.clone(),
+                None => return Err("missing value for option"),
+            };
+            match flag.as_str() {
+                "--title" => site.title = value,
+                "--base-url" => site = site.with_base_url(&value)?,
+                _ => return Err("unknown option"),
+            }
+        }
+        Ok(ExportConfig {
+            repo_dir: args[2].clone(),
+            out_dir: args[3].clone(),
+            site,
+        })
+    }
+}
+
+fn run_export(config: ExportConfig) -> Result<(), Box<dyn std::error::Error>> {
+    let repo = FileRepository::open(&config.repo_dir)?;
+    let count = export::export(&repo, Path::new(&config.out_dir), &config.site)?;
+    println!("Exported {} posts to {}", count, config.out_dir);
+    if config.site.base_url().is_none() {
+        println!("No --base-url given, skipped the RSS and Atom feeds");
+    }
+    Ok(())
+}
+
+// 没有参数时演示文章的审批流程
+fn demo() {
+    let alice = User::new("alice", Role::Author);
+    let bob = User::new("bob", Role::Reviewer);
+    let mut post = Post::new(&alice);
+
+    post.add_text(&alice, "I ate a salad for lunch today")
+        .unwrap();
+    assert_eq!("", post.content());
+
+    post.request_review(&alice).unwrap();
+    assert_eq!("", post.content());
+
+    // 作者不能审批自己的文章
+    if let Err(e) = post.approve(&alice) {
+        println!("{}", e);
+    }
+
+    post.approve(&bob).unwrap();
+    assert_eq!("I ate a salad for lunch today", post.content());
+    println!("{}", post.content());
+}
+
 fn main() {
-    println!("Hello, world!");
+    let args: Vec<String> = env::args().collect();
+    match args.get(1).map(|s| s.as_str()) {
+        Some("export") => {
+            let config = ExportConfig::new(&args).unwrap_or_else(|err| {
+                eprintln!("Problem parsing arguments: {}", err);
+                process::exit(1);
+            });
+            if let Err(e) = run_export(config) {
+                eprintln!("Application error: {}", e);
+                process::exit(1);
+            }
+        }
+        Some(_) => {
+            eprintln!("unknown command, expected `export`");
+            process::exit(1);
+        }
+        None => demo(),
+    }
 }
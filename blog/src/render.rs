@@ -0,0 +1,370 @@
+// 把文章的 Markdown 子集转换成 HTML
+// 支持：标题、强调、列表、链接、代码块、引用；默认转义原始 HTML
+use crate::Post;
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Options {
+    // 为 true 时原样输出文本中的 HTML 标签
+    pub allow_raw_html: bool,
+}
+
+// 只渲染已发布文章的内容，其他状态的 content() 为空
+pub fn render_post(post: &Post) -> String {
+    to_html(post.content())
+}
+
+pub fn to_html(markdown: &str) -> String {
+    to_html_with(markdown, &Options::default())
+}
+
+pub fn to_html_with(markdown: &str, options: &Options) -> String {
+    let lines: Vec<&str> = markdown.lines().collect();
+    let mut out = String::new();
+    render_blocks(&lines, options, &mut out);
+    out
+}
+
+pub fn escape_html(s: &str) -> String {
+    let mut out = String::new();
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+// 标题行的级别和文字，# 后面必须有空格
+pub(crate) fn heading_level(line: &str) -> Option<(usize, &str)> {
+    let level = line.chars().take_while(|c| *c == '#').count();
+    if (1..=6).contains(&level) {
+        let rest = &line[level..];
+        if rest.is_empty() {
+            return Some((level, ""));
+        }
+        if let Some(text) = rest.strip_prefix(' ') {
+            return Some((level, text.trim()));
+        }
+    }
+    None
+}
+
+fn unordered_item(line: &str) -> Option<&str> {
+    let line = line.trim_start();
+    for marker in ["- ", "* ", "+ "] {
+        if let Some(rest) = line.strip_prefix(marker) {
+            return Some(rest);
+        }
+    }
+    None
+}
+
+fn ordered_item(line: &str) -> Option<&str> {
+    let line = line.trim_start();
+    let digits = line.chars().take_while(|c| c.is_ascii_digit()).count();
+    if digits == 0 {
+        return None;
+    }
+    line[digits..].strip_prefix(". ")
+}
+
+fn is_fence(line: &str) -> bool {
+    line.trim_start().starts_with("```")
+}
+
+fn is_quote(line: &str) -> bool {
+    line.trim_start().starts_with('>')
+}
+
+// 判断一行是否开始了新的块，用于结束段落
+fn starts_block(line: &str) -> bool {
+    line.trim().is_empty()
+        || heading_level(line).is_some()
+        || unordered_item(line).is_some()
+        || ordered_item(line).is_some()
+        || is_fence(line)
+        || is_quote(line)
+}
+
+fn render_blocks(lines: &[&str], options: &Options, out: &mut String) {
+    let mut i = 0;
+    while i < lines.len() {
+        let line = lines[i];
+        if line.trim().is_empty() {
+            i += 1;
+        } else if is_fence(line) {
+            let lang = line.trim_start()[3..].trim();
+            i += 1;
+            let mut code = String::new();
+            while i < lines.len() && !is_fence(lines[i]) {
+                code.push_str(lines[i]);
+                code.push('\n');
+                i += 1;
+            }
+            // 跳过结束的 ```
+            i += 1;
+            if lang.is_empty() {
+                out.push_str("<pre><code>");
+            } else {
+                out.push_str(&format!(
+                    "<pre><code class=\"language-{}\">",
+                    escape_html(lang)
+                ));
+            }
+            out.push_str(&escape_html(&code));
+            out.push_str("</code></pre>\n");
+        } else if let Some((level, text)) = heading_level(line) {
+            out.push_str(&format!(
+                "<h{}>{}</h{}>\n",
+                level,
+                inline(text, options),
+                level
+            ));
+            i += 1;
+        } else if is_quote(line) {
+            let mut inner = Vec::new();
+            while i < lines.len() && is_quote(lines[i]) {
+                let rest = &lines[i].trim_start()[1..];
+                inner.push(rest.strip_prefix(' ').unwrap_or(rest));
+                i += 1;
+            }
+            out.push_str("<blockquote>\n");
+            render_blocks(&inner, options, out);
+            out.push_str("</blockquote>\n");
+        } else if unordered_item(line).is_some() {
+            out.push_str("<ul>\n");
+            while i < lines.len() {
+                match unordered_item(lines[i]) {
+                    Some(item) => {
+                        out.push_str(&format!("<li>{}</li>\n", inline(item, options)));
+                        i += 1;
+                    }
+                    None => break,
+                }
+            }
+            out.push_str("</ul>\n");
+        } else if ordered_item(line).is_some() {
+            out.push_str("<ol>\n");
+            while i < lines.len() {
+                match ordered_item(lines[i]) {
+                    Some(item) => {
+                        out.push_str(&format!("<li>{}</li>\n", inline(item, options)));
+                        i += 1;
+                    }
+                    None => break,
+                }
+            }
+            out.push_str("</ol>\n");
+        } else {
+            let mut text = Vec::new();
+            while i < lines.len() && (text.is_empty() || !starts_block(lines[i])) {
+                text.push(lines[i].trim());
+                i += 1;
+            }
+            out.push_str(&format!("<p>{}</p>\n", inline(&text.join("\n"), options)));
+        }
+    }
+}
+
+// 只允许安全的链接协议，避免 javascript: 之类的注入
+fn safe_url(url: &str) -> bool {
+    let lower = url.trim().to_ascii_lowercase();
+    match lower.find(':') {
+        Some(colon) => {
+            let scheme = &lower[..colon];
+            // 冒号出现在路径或查询中时视为相对链接
+            if scheme.contains('/') || scheme.contains('?') || scheme.contains('#') {
+                return true;
+            }
+            matches!(scheme, "http" | "https" | "mailto")
+        }
+        None => true,
+    }
+}
+
+fn inline(text: &str, options: &Options) -> String {
+    let mut out = String::new();
+    let mut rest = text;
+    while let Some(c) = rest.chars().next() {
+        let prev = text[..text.len() - rest.len()].chars().last();
+        if c == '\\' {
+            if let Some(next) = rest[1..].chars().next() {
+                if next.is_ascii_punctuation() {
+                    out.push_str(&escape_html(&next.to_string()));
+                    rest = &rest[1 + next.len_utf8()..];
+                    continue;
+                }
+            }
+        } else if c == '`' {
+            if let Some(end) = rest[1..].find('`') {
+                out.push_str(&format!("<code>{}</code>", escape_html(&rest[1..1 + end])));
+                rest = &rest[end + 2..];
+                continue;
+            }
+        } else if rest.starts_with("**") || rest.starts_with("__") {
+            let delim = &rest[..2];
+            if let Some(end) = rest[2..].find(delim) {
+                if end > 0 {
+                    out.push_str(&format!(
+                        "<strong>{}</strong>",
+                        inline(&rest[2..2 + end], options)
+                    ));
+                    rest = &rest[end + 4..];
+                    continue;
+                }
+            }
+        } else if c == '*' || c == '_' {
+            // 单词中间的下划线（如 snake_case）不当作强调
+            let in_word = c == '_' && prev.is_some_and(|p| p.is_alphanumeric());
+            if !in_word {
+                if let Some(end) = rest[1..].find(c) {
+                    if end > 0 {
+                        out.push_str(&format!("<em>{}</em>", inline(&rest[1..1 + end], options)));
+                        rest = &rest[end + 2..];
+                        continue;
+                    }
+                }
+            }
+        } else if c == '[' {
+            if let Some((label, url, len)) = parse_link(rest) {
+                if safe_url(url) {
+                    out.push_str(&format!(
+                        "<a href=\"{}\">{}</a>",
+                        escape_html(url.trim()),
+                        inline(label, options)
+                    ));
+                } else {
+                    out.push_str(&inline(label, options));
+                }
+                rest = &rest[len..];
+                continue;
+            }
+        } else if c == '<' && options.allow_raw_html {
+            if let Some(end) = rest.find('>') {
+                out.push_str(&rest[..=end]);
+                rest = &rest[end + 1..];
+                continue;
+            }
+        }
+        out.push_str(&escape_html(&c.to_string()));
+        rest = &rest[c.len_utf8()..];
+    }
+    out
+}
+
+// 解析 [label](url)，返回标签、地址和消耗的字节数
+fn parse_link(s: &str) -> Option<(&str, &str, usize)> {
+    // 找到与开头 '[' 配对的 ']'，它后面必须紧跟 '('
+    let mut depth = 0;
+    let mut close = None;
+    for (i, c) in s.char_indices().skip(1) {
+        match c {
+            '[' => depth += 1,
+            ']' if depth == 0 => {
+                close = Some(i);
+                break;
+            }
+            ']' => depth -= 1,
+            _ => {}
+        }
+    }
+    let close = close?;
+    if !s[close + 1..].starts_with('(') {
+        return None;
+    }
+    let label = &s[1..close];
+    let url_start = close + 2;
+    // 地址中允许成对出现的括号
+    let mut depth = 0;
+    for (i, c) in s[url_start..].char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' if depth == 0 => {
+                return Some((label, &s[url_start..url_start + i], url_start + i + 1))
+            }
+            ')' => depth -= 1,
+            _ => {}
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::{Role, User};
+
+    #[test]
+    fn headings_and_paragraphs() {
+        let html = to_html("# Title\n\nfirst line\nsecond line\n\n## Sub");
+        assert_eq!(
+            "<h1>Title</h1>\n<p>first line\nsecond line</p>\n<h2>Sub</h2>\n",
+            html
+        );
+    }
+
+    #[test]
+    fn inline_markup() {
+        assert_eq!(
+            "<p><strong>bold</strong>, <em>em</em>, <code>a &lt; b</code> and snake_case_name</p>\n",
+            to_html("**bold**, *em*, `a < b` and snake_case_name")
+        );
+        assert_eq!(
+            "<p><a href=\"https://rust-lang.org\">Rust <em>lang</em></a></p>\n",
+            to_html("[Rust _lang_](https://rust-lang.org)")
+        );
+    }
+
+    #[test]
+    fn link_label_ends_at_matching_bracket() {
+        assert_eq!(
+            "<p>[a] and <a href=\"u\">b</a></p>\n",
+            to_html("[a] and [b](u)")
+        );
+        assert_eq!(
+            "<p><a href=\"u\">see [1]</a></p>\n",
+            to_html("[see [1]](u)")
+        );
+    }
+
+    #[test]
+    fn lists_quotes_and_code() {
+        let md = "- one\n- two\n\n1. first\n2. second\n\n> quoted *text*\n> - item\n\n```rust\nfn main() {}\n```";
+        assert_eq!(
+            "<ul>\n<li>one</li>\n<li>two</li>\n</ul>\n\
+             <ol>\n<li>first</li>\n<li>second</li>\n</ol>\n\
+             <blockquote>\n<p>quoted <em>text</em></p>\n<ul>\n<li>item</li>\n</ul>\n</blockquote>\n\
+             <pre><code class=\"language-rust\">fn main() {}\n</code></pre>\n",
+            to_html(md)
+        );
+    }
+
+    #[test]
+    fn raw_html_is_escaped_by_default() {
+        let md = "<script>alert('x')</script> [click](javascript:alert(1))";
+        assert_eq!(
+            "<p>&lt;script&gt;alert(&#39;x&#39;)&lt;/script&gt; click</p>\n",
+            to_html(md)
+        );
+        let options = Options {
+            allow_raw_html: true,
+        };
+        assert_eq!("<p><b>hi</b></p>\n", to_html_with("<b>hi</b>", &options));
+    }
+
+    #[test]
+    fn only_published_posts_render() {
+        let alice = User::new("alice", Role::Author);
+        let mut post = Post::new(&alice);
+        post.add_text(&alice, "# Hello").unwrap();
+        assert_eq!("", render_post(&post));
+        post.request_review(&alice).unwrap();
+        post.approve(&User::new("bob", Role::Reviewer)).unwrap();
+        assert_eq!("<h1>Hello</h1>\n", render_post(&post));
+    }
+}
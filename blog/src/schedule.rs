@@ -0,0 +1,167 @@
+// 定时发布：通过可替换的时钟推进文章状态，测试中可以手动控制时间
+use crate::storage::{PostRepository, StoreError};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+// Post 内部也保存时钟，所以要求可以跨线程共享
+pub trait Clock: Send + Sync {
+    // 当前时间，Unix 秒
+    fn now(&self) -> u64;
+}
+
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> u64 {
+        crate::history::now()
+    }
+}
+
+// 手动控制的时钟，用于测试
+pub struct ManualClock {
+    now: AtomicU64,
+}
+
+impl ManualClock {
+    pub fn new(now: u64) -> ManualClock {
+        ManualClock {
+            now: AtomicU64::new(now),
+        }
+    }
+    pub fn set(&self, now: u64) {
+        self.now.store(now, Ordering::Relaxed);
+    }
+    pub fn advance(&self, secs: u64) {
+        self.now.fetch_add(secs, Ordering::Relaxed);
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> u64 {
+        self.now.load(Ordering::Relaxed)
+    }
+}
+
+pub struct Scheduler<C: Clock> {
+    clock: C,
+}
+
+impl<C: Clock> Scheduler<C> {
+    pub fn new(clock: C) -> Scheduler<C> {
+        Scheduler { clock }
+    }
+
+    pub fn clock(&self) -> &C {
+        &self.clock
+    }
+
+    // 发布仓库中所有到期的定时文章，返回被发布的文章 id
+    pub fn run<R: PostRepository>(&self, repo: &mut R) -> Result<Vec<u64>, StoreError> {
+        let now = self.clock.now();
+        let mut published = Vec::new();
+        for id in repo.ids()? {
+            if let Some(mut post) = repo.get(id)? {
+                if post.publish_at().is_some_and(|t| t <= now) {
+                    post.tick(now);
+                    repo.update(id, &post)?;
+                    published.push(id);
+                }
+            }
+        }
+        Ok(published)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::{Role, User};
+    use crate::history::Action;
+    use crate::storage::MemoryRepository;
+    use crate::Post;
+    use std::sync::Arc;
+
+    fn alice() -> User {
+        User::new("alice", Role::Author)
+    }
+
+    fn scheduled_post(text: &str, publish_at: u64) -> Post {
+        let mut post = Post::new(&alice());
+        post.add_text(&alice(), text).unwrap();
+        post.request_review(&alice()).unwrap();
+        post.schedule(&User::new("bob", Role::Reviewer), publish_at)
+            .unwrap();
+        post
+    }
+
+    #[test]
+    fn tick_publishes_when_due() {
+        let mut post = scheduled_post("embargoed", 100);
+        assert_eq!("scheduled", post.state_name());
+        post.tick(99);
+        assert_eq!("", post.content());
+        post.tick(100);
+        assert_eq!("embargoed", post.content());
+        let last = post.history().last().unwrap();
+        assert_eq!(100, last.timestamp);
+        assert_eq!("scheduler", last.actor);
+    }
+
+    #[test]
+    fn unpublish_returns_to_draft() {
+        let editor = User::new("carol", Role::Editor);
+        let mut post = scheduled_post("text", 100);
+        post.unpublish(&editor).unwrap();
+        assert_eq!("draft", post.state_name());
+        assert_eq!(None, post.publish_at());
+
+        let mut post = scheduled_post("text", 100);
+        post.tick(200);
+        post.unpublish(&editor).unwrap();
+        assert_eq!("draft", post.state_name());
+        post.add_text(&alice(), " edited").unwrap();
+        post.request_review(&alice()).unwrap();
+        post.approve(&editor).unwrap();
+        assert_eq!("text edited", post.content());
+    }
+
+    #[test]
+    fn post_records_clock_time() {
+        let clock = Arc::new(ManualClock::new(10));
+        let mut post = Post::with_clock(&alice(), clock.clone());
+        clock.advance(5);
+        post.add_text(&alice(), "text").unwrap();
+        clock.set(100);
+        post.request_review(&alice()).unwrap();
+        let times: Vec<(Action, u64)> = post
+            .history()
+            .iter()
+            .map(|r| (r.action, r.timestamp))
+            .collect();
+        assert_eq!(
+            vec![
+                (Action::Create, 10),
+                (Action::AddText, 15),
+                (Action::RequestReview, 100)
+            ],
+            times
+        );
+    }
+
+    #[test]
+    fn scheduler_publishes_due_posts() {
+        let mut repo = MemoryRepository::new();
+        let soon = repo.insert(&scheduled_post("soon", 1_000)).unwrap();
+        let later = repo.insert(&scheduled_post("later", 5_000)).unwrap();
+
+        let scheduler = Scheduler::new(ManualClock::new(0));
+        assert!(scheduler.run(&mut repo).unwrap().is_empty());
+
+        scheduler.clock().set(1_000);
+        assert_eq!(vec![soon], scheduler.run(&mut repo).unwrap());
+        assert_eq!("soon", repo.get(soon).unwrap().unwrap().content());
+        assert_eq!("scheduled", repo.get(later).unwrap().unwrap().state_name());
+
+        scheduler.clock().advance(4_000);
+        assert_eq!(vec![later], scheduler.run(&mut repo).unwrap());
+    }
+}
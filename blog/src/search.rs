@@ -0,0 +1,397 @@
+// 文章全文检索：倒排索引 + BM25 排序，支持短语查询和按状态、标签、作者过滤
+// 索引的是原始正文，草稿也能被搜索到
+use crate::storage::{PostRepository, StoreError};
+use crate::Post;
+use std::collections::{BTreeMap, HashMap};
+
+const K1: f64 = 1.2;
+const B: f64 = 0.75;
+
+const STOP_WORDS: [&str; 33] = [
+    "a", "an", "and", "are", "as", "at", "be", "but", "by", "for", "if", "in", "into", "is", "it",
+    "no", "not", "of", "on", "or", "such", "that", "the", "their", "then", "there", "these",
+    "they", "this", "to", "was", "will", "with",
+];
+
+// 简单的英文词干提取，只去掉常见后缀
+pub fn stem(word: &str) -> String {
+    let rules: [(&str, &str); 10] = [
+        ("sses", "ss"),
+        ("ches", "ch"),
+        ("shes", "sh"),
+        ("xes", "x"),
+        ("ies", "y"),
+        ("ing", ""),
+        ("edly", ""),
+        ("ed", ""),
+        ("ly", ""),
+        ("s", ""),
+    ];
+    for (suffix, replacement) in rules {
+        if let Some(base) = word.strip_suffix(suffix) {
+            // 词干太短时保持原样，避免 "is" -> "i" 之类的问题
+            if base.chars().count() >= 3 && !(suffix == "s" && base.ends_with('s')) {
+                return format!("{}{}", base, replacement);
+            }
+        }
+    }
+    String::from(word)
+}
+
+// 分词：转小写、按非字母数字切分、去掉停用词并提取词干
+pub fn tokenize(text: &str) -> Vec<String> {
+    positioned(text).into_iter().map(|(_, term)| term).collect()
+}
+
+// 同 tokenize，但保留每个词在原文中的位置，停用词虽然去掉了仍然占一个位置，
+// 这样短语 "borrow the checker" 不会匹配到 "borrow checker"
+fn positioned(text: &str) -> Vec<(usize, String)> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(|w| w.to_lowercase())
+        .enumerate()
+        .filter(|(_, w)| !STOP_WORDS.contains(&w.as_str()))
+        .map(|(pos, w)| (pos, stem(&w)))
+        .collect()
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Query {
+    pub terms: Vec<String>,
+    // 短语中的每个词和它相对短语开头的位置
+    pub phrases: Vec<Vec<(usize, String)>>,
+    pub state: Option<String>,
+    pub tag: Option<String>,
+    pub author: Option<String>,
+}
+
+impl Query {
+    // 双引号中的内容作为短语，其余为普通关键词
+    pub fn parse(text: &str) -> Query {
+        let mut query = Query::default();
+        for (i, part) in text.split('"').enumerate() {
+            if i % 2 == 1 {
+                let mut phrase = positioned(part);
+                if let Some(&(first, _)) = phrase.first() {
+                    for (pos, _) in &mut phrase {
+                        *pos -= first;
+                    }
+                    query.phrases.push(phrase);
+                }
+            } else {
+                query.terms.extend(tokenize(part));
+            }
+        }
+        query
+    }
+
+    pub fn state(mut self, state: &str) -> Query {
+        self.state = Some(String::from(state));
+        self
+    }
+
+    pub fn tag(mut self, tag: &str) -> Query {
+        self.tag = Some(String::from(tag));
+        self
+    }
+
+    pub fn author(mut self, author: &str) -> Query {
+        self.author = Some(String::from(author));
+        self
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Hit {
+    pub id: u64,
+    pub score: f64,
+}
+
+struct Doc {
+    terms: Vec<String>,
+    state: String,
+    author: String,
+    tags: Vec<String>,
+}
+
+#[derive(Default)]
+pub struct SearchIndex {
+    docs: HashMap<u64, Doc>,
+    // 词 -> 文章 id -> 出现的位置
+    postings: HashMap<String, BTreeMap<u64, Vec<usize>>>,
+    total_len: usize,
+}
+
+impl SearchIndex {
+    pub fn new() -> SearchIndex {
+        SearchIndex::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.docs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.docs.is_empty()
+    }
+
+    // 重新索引一篇文章，只更新这篇文章相关的倒排项
+    pub fn update(&mut self, id: u64, post: &Post) {
+        self.remove(id);
+        let tokens = positioned(&post.content);
+        for (pos, term) in &tokens {
+            self.postings
+                .entry(term.clone())
+                .or_default()
+                .entry(id)
+                .or_default()
+                .push(*pos);
+        }
+        let terms: Vec<String> = tokens.into_iter().map(|(_, term)| term).collect();
+        self.total_len += terms.len();
+        self.docs.insert(
+            id,
+            Doc {
+                terms,
+                state: String::from(post.state_name()),
+                author: String::from(post.author()),
+                tags: post.tags().to_vec(),
+            },
+        );
+    }
+
+    pub fn remove(&mut self, id: u64) {
+        if let Some(doc) = self.docs.remove(&id) {
+            self.total_len -= doc.terms.len();
+            for term in doc.terms {
+                if let Some(docs) = self.postings.get_mut(&term) {
+                    docs.remove(&id);
+                    if docs.is_empty() {
+                        self.postings.remove(&term);
+                    }
+                }
+            }
+        }
+    }
+
+    fn matches_filters(&self, doc: &Doc, query: &Query) -> bool {
+        query.state.as_ref().is_none_or(|s| *s == doc.state)
+            && query.author.as_ref().is_none_or(|a| *a == doc.author)
+            && query
+                .tag
+                .as_ref()
+                .is_none_or(|t| doc.tags.iter().any(|d| d.eq_ignore_ascii_case(t)))
+    }
+
+    // 停用词的位置不检查具体是哪个词，"borrow the checker" 也会匹配 "borrow a checker"
+    fn contains_phrase(&self, id: u64, phrase: &[(usize, String)]) -> bool {
+        let mut lists = Vec::new();
+        for (offset, term) in phrase {
+            match self.postings.get(term).and_then(|docs| docs.get(&id)) {
+                Some(positions) => lists.push((*offset, positions)),
+                None => return false,
+            }
+        }
+        lists[0].1.iter().any(|&start| {
+            lists[1..]
+                .iter()
+                .all(|(offset, positions)| positions.contains(&(start + offset)))
+        })
+    }
+
+    fn bm25(&self, id: u64, term: &str) -> f64 {
+        let docs = match self.postings.get(term) {
+            Some(docs) => docs,
+            None => return 0.0,
+        };
+        let tf = match docs.get(&id) {
+            Some(positions) => positions.len() as f64,
+            None => return 0.0,
+        };
+        let n = self.docs.len() as f64;
+        let df = docs.len() as f64;
+        let idf = ((n - df + 0.5) / (df + 0.5) + 1.0).ln();
+        let avg_len = self.total_len as f64 / n;
+        let len = self.docs[&id].terms.len() as f64;
+        idf * tf * (K1 + 1.0) / (tf + K1 * (1.0 - B + B * len / avg_len))
+    }
+
+    // 关键词之间是“或”的关系，短语必须全部出现；
+    // 去掉停用词后没有任何关键词和短语时不返回结果
+    pub fn search(&self, query: &Query) -> Vec<Hit> {
+        let scored: Vec<&String> = query
+            .terms
+            .iter()
+            .chain(query.phrases.iter().flatten().map(|(_, term)| term))
+            .collect();
+        let mut hits = Vec::new();
+        if scored.is_empty() {
+            return hits;
+        }
+        for (&id, doc) in &self.docs {
+            if !self.matches_filters(doc, query) {
+                continue;
+            }
+            if !query.phrases.iter().all(|p| self.contains_phrase(id, p)) {
+                continue;
+            }
+            let score: f64 = scored.iter().map(|t| self.bm25(id, t)).sum();
+            if !query.terms.is_empty() && query.phrases.is_empty() && score == 0.0 {
+                continue;
+            }
+            hits.push(Hit { id, score });
+        }
+        hits.sort_by(|a, b| b.score.total_cmp(&a.score).then(a.id.cmp(&b.id)));
+        hits
+    }
+}
+
+// 包装一个仓库，保存文章时同步更新索引
+pub struct IndexedRepository<R: PostRepository> {
+    inner: R,
+    index: SearchIndex,
+}
+
+impl<R: PostRepository> IndexedRepository<R> {
+    // 为仓库中已有的文章建立索引
+    pub fn new(inner: R) -> Result<IndexedRepository<R>, StoreError> {
+        let mut index = SearchIndex::new();
+        for id in inner.ids()? {
+            if let Some(post) = inner.get(id)? {
+                index.update(id, &post);
+            }
+        }
+        Ok(IndexedRepository { inner, index })
+    }
+
+    pub fn index(&self) -> &SearchIndex {
+        &self.index
+    }
+
+    pub fn search(&self, query: &Query) -> Vec<Hit> {
+        self.index.search(query)
+    }
+}
+
+impl<R: PostRepository> PostRepository for IndexedRepository<R> {
+    fn insert(&mut self, post: &Post) -> Result<u64, StoreError> {
+        let id = self.inner.insert(post)?;
+        self.index.update(id, post);
+        Ok(id)
+    }
+    fn update(&mut self, id: u64, post: &Post) -> Result<(), StoreError> {
+        self.inner.update(id, post)?;
+        self.index.update(id, post);
+        Ok(())
+    }
+    fn get(&self, id: u64) -> Result<Option<Post>, StoreError> {
+        self.inner.get(id)
+    }
+    fn remove(&mut self, id: u64) -> Result<(), StoreError> {
+        self.inner.remove(id)?;
+        self.index.remove(id);
+        Ok(())
+    }
+    fn ids(&self) -> Result<Vec<u64>, StoreError> {
+        self.inner.ids()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::{Role, User};
+    use crate::storage::MemoryRepository;
+
+    fn alice() -> User {
+        User::new("alice", Role::Author)
+    }
+
+    fn draft(author: &User, text: &str, tag: &str) -> Post {
+        let mut post = Post::new(author);
+        post.add_text(author, text).unwrap();
+        post.add_tag(tag);
+        post
+    }
+
+    #[test]
+    fn tokenizes_and_stems() {
+        assert_eq!(
+            vec!["borrow", "checker", "catch", "bug", "quick"],
+            tokenize("The Borrowing checker catches bugs quickly!")
+        );
+        assert_eq!("class", stem("classes"));
+        assert_eq!("story", stem("stories"));
+    }
+
+    #[test]
+    fn ranks_with_bm25() {
+        let mut index = SearchIndex::new();
+        index.update(1, &draft(&alice(), "rust rust rust ownership", "rust"));
+        index.update(
+            2,
+            &draft(&alice(), "rust and go and python and java", "lang"),
+        );
+        index.update(3, &draft(&alice(), "gardening tips", "home"));
+
+        let hits = index.search(&Query::parse("rust"));
+        let ids: Vec<u64> = hits.iter().map(|h| h.id).collect();
+        assert_eq!(vec![1, 2], ids);
+        assert!(hits[0].score > hits[1].score);
+    }
+
+    #[test]
+    fn phrase_queries_and_filters() {
+        let bob = User::new("bob", Role::Author);
+        let mut index = SearchIndex::new();
+        index.update(1, &draft(&alice(), "the borrow checker is strict", "rust"));
+        index.update(2, &draft(&bob, "checker of borrow rules", "rust"));
+
+        let ids = |q: Query| -> Vec<u64> { index.search(&q).iter().map(|h| h.id).collect() };
+        assert_eq!(vec![1], ids(Query::parse("\"borrow checker\"")));
+        assert_eq!(vec![2], ids(Query::parse("\"checker of borrow\"")));
+        // 停用词占的位置也要对上
+        assert!(ids(Query::parse("\"borrow the checker\"")).is_empty());
+        assert!(ids(Query::parse("\"checker borrow\"")).is_empty());
+        assert_eq!(vec![2], ids(Query::parse("borrow").author("bob")));
+        assert_eq!(
+            vec![1, 2],
+            ids(Query::parse("checker").tag("RUST").state("draft"))
+        );
+        assert!(ids(Query::parse("borrow").state("published")).is_empty());
+    }
+
+    #[test]
+    fn empty_queries_find_nothing() {
+        let mut index = SearchIndex::new();
+        index.update(1, &draft(&alice(), "the borrow checker", "rust"));
+        assert!(index.search(&Query::parse("")).is_empty());
+        assert!(index.search(&Query::parse("the \"and\" of")).is_empty());
+        assert!(index.search(&Query::default().tag("rust")).is_empty());
+        assert_eq!(1, index.search(&Query::parse("\"the borrow\"")).len());
+    }
+
+    #[test]
+    fn index_follows_repository_updates() {
+        let mut repo = IndexedRepository::new(MemoryRepository::new()).unwrap();
+        let mut post = draft(&alice(), "first draft", "notes");
+        let id = repo.insert(&post).unwrap();
+        assert_eq!(1, repo.search(&Query::parse("draft")).len());
+
+        post.add_text(&alice(), " about lifetimes").unwrap();
+        post.request_review(&alice()).unwrap();
+        repo.update(id, &post).unwrap();
+        assert_eq!(
+            1,
+            repo.search(&Query::parse("lifetime").state("pending_review"))
+                .len()
+        );
+        assert!(repo
+            .search(&Query::parse("draft").state("draft"))
+            .is_empty());
+
+        repo.remove(id).unwrap();
+        assert!(repo.index().is_empty());
+        assert!(repo.search(&Query::parse("lifetime")).is_empty());
+    }
+}
@@ -0,0 +1,511 @@
+// 只使用标准库网络的简单 HTTP/1.1 接口，用来在其他语言中驱动文章的审批流程
+use crate::auth::{Role, TransitionError, User};
+use crate::schedule::{Scheduler, SystemClock};
+use crate::storage::{PostRepository, StoreError};
+use crate::Post;
+use std::fmt;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::time::{Duration, Instant};
+
+// 请求体的上限，超过时返回 413
+pub const MAX_BODY: usize = 1024 * 1024;
+// 请求行和所有请求头加起来的上限，超过时返回 431
+pub const MAX_HEADER: usize = 8 * 1024;
+// 连接是逐个处理的，读写超时避免一个慢客户端卡住整个服务
+const TIMEOUT: Duration = Duration::from_secs(5);
+// 读完整个请求的期限，防止客户端每次只发一点数据拖住连接
+const DEADLINE: Duration = Duration::from_secs(10);
+
+pub struct Request {
+    pub method: String,
+    pub path: String,
+    pub body: String,
+    // 来自 X-User 与 X-Role 请求头，仅用于本机调用，不做身份验证
+    pub user: Option<User>,
+}
+
+pub struct Response {
+    pub status: u16,
+    pub body: String,
+}
+
+impl Response {
+    fn json(status: u16, body: String) -> Response {
+        Response { status, body }
+    }
+
+    fn error(status: u16, message: &str) -> Response {
+        Response::json(status, format!("{{\"error\":{}}}", json_string(message)))
+    }
+
+    fn reason(&self) -> &'static str {
+        match self.status {
+            200 => "OK",
+            201 => "Created",
+            400 => "Bad Request",
+            401 => "Unauthorized",
+            403 => "Forbidden",
+            404 => "Not Found",
+            405 => "Method Not Allowed",
+            409 => "Conflict",
+            413 => "Payload Too Large",
+            431 => "Request Header Fields Too Large",
+            _ => "Internal Server Error",
+        }
+    }
+}
+
+pub fn json_string(s: &str) -> String {
+    let mut out = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+fn post_json(id: u64, post: &Post) -> String {
+    format!(
+        "{{\"id\":{},\"state\":{},\"content\":{}}}",
+        id,
+        json_string(post.state_name()),
+        json_string(post.content())
+    )
+}
+
+#[derive(Debug)]
+pub enum RequestError {
+    Io(io::Error),
+    // Content-Length 超过 MAX_BODY
+    TooLarge(usize),
+    // 请求行和请求头超过 MAX_HEADER
+    HeadersTooLarge,
+}
+
+impl fmt::Display for RequestError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RequestError::Io(e) => write!(f, "{}", e),
+            RequestError::TooLarge(length) => write!(
+                f,
+                "request body of {} bytes exceeds the limit of {} bytes",
+                length, MAX_BODY
+            ),
+            RequestError::HeadersTooLarge => write!(
+                f,
+                "request headers exceed the limit of {} bytes",
+                MAX_HEADER
+            ),
+        }
+    }
+}
+
+impl std::error::Error for RequestError {}
+
+impl From<io::Error> for RequestError {
+    fn from(e: io::Error) -> Self {
+        RequestError::Io(e)
+    }
+}
+
+impl RequestError {
+    fn response(&self) -> Response {
+        let status = match self {
+            RequestError::Io(_) => 400,
+            RequestError::TooLarge(_) => 413,
+            RequestError::HeadersTooLarge => 431,
+        };
+        Response::error(status, &self.to_string())
+    }
+}
+
+// 读一行，从 budget 中扣除读到的字节；用完时还没读到换行说明请求头太长
+fn read_header_line(
+    reader: &mut impl BufRead,
+    line: &mut String,
+    budget: &mut usize,
+) -> Result<usize, RequestError> {
+    let n = reader.take(*budget as u64).read_line(line)?;
+    *budget -= n;
+    if *budget == 0 && !line.ends_with('\n') {
+        return Err(RequestError::HeadersTooLarge);
+    }
+    Ok(n)
+}
+
+pub fn read_request(reader: &mut impl BufRead) -> Result<Request, RequestError> {
+    let invalid = |msg: &str| io::Error::new(io::ErrorKind::InvalidData, msg.to_string());
+    let mut budget = MAX_HEADER;
+    let mut line = String::new();
+    read_header_line(reader, &mut line, &mut budget)?;
+    let mut parts = line.split_whitespace();
+    let method = parts.next().ok_or_else(|| invalid("missing method"))?;
+    let path = parts.next().ok_or_else(|| invalid("missing path"))?;
+    let (method, path) = (method.to_string(), path.to_string());
+
+    let mut length = 0;
+    let mut name = None;
+    let mut role = None;
+    loop {
+        let mut header = String::new();
+        if read_header_line(reader, &mut header, &mut budget)? == 0 {
+            break;
+        }
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((key, value)) = header.split_once(':') {
+            let value = value.trim();
+            if key.eq_ignore_ascii_case("content-length") {
+                length = value.parse().map_err(|_| invalid("bad content-length"))?;
+            } else if key.eq_ignore_ascii_case("x-user") {
+                name = Some(value.to_string());
+            } else if key.eq_ignore_ascii_case("x-role") {
+                role = Some(Role::parse(value).ok_or_else(|| invalid("unknown role"))?);
+            }
+        }
+    }
+
+    if length > MAX_BODY {
+        return Err(RequestError::TooLarge(length));
+    }
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body)?;
+    let body = String::from_utf8(body).map_err(|_| invalid("body is not utf-8"))?;
+    let user = match (name, role) {
+        (Some(name), Some(role)) => Some(User::new(&name, role)),
+        _ => None,
+    };
+    Ok(Request {
+        method,
+        path,
+        body,
+        user,
+    })
+}
+
+pub fn write_response(stream: &mut impl Write, response: &Response) -> io::Result<()> {
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        response.status,
+        response.reason(),
+        response.body.len(),
+        response.body
+    )?;
+    stream.flush()
+}
+
+// 路由：
+//   POST /posts                       创建草稿
+//   POST /posts/{id}/text             追加正文（请求体为纯文本）
+//   POST /posts/{id}/request_review   提交审核
+//   POST /posts/{id}/approve          通过
+//   POST /posts/{id}/reject           退回
+//   POST /posts/{id}/schedule         定时发布（请求体为 Unix 秒）
+//   POST /posts/{id}/unpublish        撤回到草稿
+//   GET  /posts                       已发布文章列表
+//   GET  /posts/{id}                  获取已发布文章
+pub fn handle<R: PostRepository>(repo: &mut R, request: &Request) -> Response {
+    match route(repo, request) {
+        Ok(response) => response,
+        Err(e) => Response::error(500, &e.to_string()),
+    }
+}
+
+fn route<R: PostRepository>(repo: &mut R, request: &Request) -> Result<Response, StoreError> {
+    // 查询参数目前没有用到，路由前去掉
+    let path = request.path.split('?').next().unwrap_or("");
+    let segments: Vec<&str> = path
+        .trim_matches('/')
+        .split('/')
+        .filter(|s| !s.is_empty())
+        .collect();
+    if segments.first() != Some(&"posts") {
+        return Ok(Response::error(404, "not found"));
+    }
+    let id = match segments.get(1) {
+        Some(id) => match id.parse::<u64>() {
+            Ok(id) => Some(id),
+            Err(_) => return Ok(Response::error(400, "invalid post id")),
+        },
+        None => None,
+    };
+    let method = request.method.as_str();
+
+    match (method, id, segments.get(2).copied(), segments.len()) {
+        ("POST", None, None, 1) => {
+            let user = match &request.user {
+                Some(user) => user,
+                None => return Ok(Response::error(401, "missing X-User or X-Role header")),
+            };
+            let post = Post::new(user);
+            let id = repo.insert(&post)?;
+            Ok(Response::json(201, post_json(id, &post)))
+        }
+        ("GET", None, None, 1) => {
+            let mut items = Vec::new();
+            for id in repo.ids()? {
+                if let Some(post) = repo.get(id)? {
+                    if post.state_name() == "published" {
+                        items.push(post_json(id, &post));
+                    }
+                }
+            }
+            Ok(Response::json(200, format!("[{}]", items.join(","))))
+        }
+        ("GET", Some(id), None, 2) => match repo.get(id)? {
+            Some(post) if post.state_name() == "published" => {
+                Ok(Response::json(200, post_json(id, &post)))
+            }
+            _ => Ok(Response::error(404, "post not found")),
+        },
+        ("POST", Some(id), Some(action), 3) => {
+            let mut post = match repo.get(id)? {
+                Some(post) => post,
+                None => return Ok(Response::error(404, "post not found")),
+            };
+            let user = match &request.user {
+                Some(user) => user,
+                None => return Ok(Response::error(401, "missing X-User or X-Role header")),
+            };
+            let result = match action {
+                "text" => post.add_text(user, &request.body),
+                "request_review" => post.request_review(user),
+                "approve" => post.approve(user),
+                "reject" => post.reject(user),
+                "schedule" => match request.body.trim().parse() {
+                    Ok(publish_at) => post.schedule(user, publish_at),
+                    Err(_) => return Ok(Response::error(400, "invalid publish time")),
+                },
+                "unpublish" => post.unpublish(user),
+                _ => return Ok(Response::error(404, "unknown action")),
+            };
+            if let Err(e) = result {
+                let status = match e {
+                    TransitionError::InvalidTransition { .. } => 409,
+                    TransitionError::Forbidden { .. } | TransitionError::SelfApproval => 403,
+                };
+                return Ok(Response::error(status, &e.to_string()));
+            }
+            repo.update(id, &post)?;
+            Ok(Response::json(
+                200,
+                format!(
+                    "{{\"id\":{},\"state\":{}}}",
+                    id,
+                    json_string(post.state_name())
+                ),
+            ))
+        }
+        (_, _, _, n) if n <= 3 => Ok(Response::error(405, "method not allowed")),
+        _ => Ok(Response::error(404, "not found")),
+    }
+}
+
+// 每次读取前把超时缩短到剩余的时间，过了期限直接报超时
+struct Deadline {
+    stream: TcpStream,
+    until: Instant,
+}
+
+impl Read for Deadline {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let left = self.until.saturating_duration_since(Instant::now());
+        if left.is_zero() {
+            return Err(io::Error::new(
+                io::ErrorKind::TimedOut,
+                "request took too long",
+            ));
+        }
+        self.stream.set_read_timeout(Some(left.min(TIMEOUT)))?;
+        self.stream.read(buf)
+    }
+}
+
+pub fn handle_connection<R: PostRepository>(stream: TcpStream, repo: &mut R) -> io::Result<()> {
+    stream.set_write_timeout(Some(TIMEOUT))?;
+    let mut reader = BufReader::new(Deadline {
+        stream: stream.try_clone()?,
+        until: Instant::now() + DEADLINE,
+    });
+    let response = match read_request(&mut reader) {
+        Ok(request) => handle(repo, &request),
+        Err(e) => e.response(),
+    };
+    let mut stream = stream;
+    write_response(&mut stream, &response)
+}
+
+// 按顺序处理每个连接，处理前先发布到期的定时文章
+pub fn serve<R: PostRepository>(listener: TcpListener, repo: &mut R) -> io::Result<()> {
+    let scheduler = Scheduler::new(SystemClock);
+    for stream in listener.incoming() {
+        if let Err(e) = scheduler.run(repo) {
+            eprintln!("scheduler error: {}", e);
+        }
+        let result = stream.and_then(|stream| handle_connection(stream, repo));
+        if let Err(e) = result {
+            eprintln!("connection error: {}", e);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::MemoryRepository;
+    use std::io::Read;
+    use std::thread;
+
+    fn request(method: &str, path: &str, body: &str) -> Request {
+        as_user("alice", Role::Author, method, path, body)
+    }
+
+    fn as_user(name: &str, role: Role, method: &str, path: &str, body: &str) -> Request {
+        Request {
+            method: method.to_string(),
+            path: path.to_string(),
+            body: body.to_string(),
+            user: Some(User::new(name, role)),
+        }
+    }
+
+    #[test]
+    fn workflow_over_routes() {
+        let mut repo = MemoryRepository::new();
+        let created = handle(&mut repo, &request("POST", "/posts", ""));
+        assert_eq!(201, created.status);
+        assert_eq!(
+            "{\"id\":1,\"state\":\"draft\",\"content\":\"\"}",
+            created.body
+        );
+
+        let bob = |method: &str, path: &str| as_user("bob", Role::Reviewer, method, path, "");
+        let status = |repo: &mut MemoryRepository, req: Request| handle(repo, &req).status;
+
+        assert_eq!(
+            200,
+            status(&mut repo, request("POST", "/posts/1/text", "hi \"there\""))
+        );
+        assert_eq!(404, status(&mut repo, request("GET", "/posts/1", "")));
+        assert_eq!(409, status(&mut repo, bob("POST", "/posts/1/approve")));
+        assert_eq!(
+            200,
+            status(&mut repo, request("POST", "/posts/1/request_review", ""))
+        );
+        assert_eq!(
+            409,
+            status(&mut repo, request("POST", "/posts/1/text", "more"))
+        );
+        // 作者不能审批自己的文章
+        assert_eq!(
+            403,
+            status(&mut repo, request("POST", "/posts/1/approve", ""))
+        );
+        assert_eq!(
+            400,
+            status(
+                &mut repo,
+                as_user("bob", Role::Reviewer, "POST", "/posts/1/schedule", "soon")
+            )
+        );
+        assert_eq!(200, status(&mut repo, bob("POST", "/posts/1/approve")));
+
+        let listed = handle(&mut repo, &request("GET", "/posts", ""));
+        assert_eq!(
+            "[{\"id\":1,\"state\":\"published\",\"content\":\"hi \\\"there\\\"\"}]",
+            listed.body
+        );
+        assert_eq!(404, status(&mut repo, bob("POST", "/posts/9/approve")));
+        assert_eq!(400, status(&mut repo, request("GET", "/posts/x", "")));
+        assert_eq!(405, status(&mut repo, request("DELETE", "/posts/1", "")));
+        assert_eq!(
+            200,
+            status(&mut repo, request("GET", "/posts/1?format=json", ""))
+        );
+        assert_eq!(200, status(&mut repo, request("GET", "/posts?page=2", "")));
+
+        assert_eq!(403, status(&mut repo, bob("POST", "/posts/1/unpublish")));
+        let mut anonymous = request("POST", "/posts/1/unpublish", "");
+        anonymous.user = None;
+        assert_eq!(401, status(&mut repo, anonymous));
+        assert_eq!(
+            200,
+            status(
+                &mut repo,
+                as_user("carol", Role::Editor, "POST", "/posts/1/unpublish", "")
+            )
+        );
+    }
+
+    #[test]
+    fn serves_over_tcp() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = thread::spawn(move || {
+            let mut repo = MemoryRepository::new();
+            let (stream, _) = listener.accept().unwrap();
+            handle_connection(stream, &mut repo).unwrap();
+        });
+
+        let mut client = TcpStream::connect(addr).unwrap();
+        client
+            .write_all(
+                b"POST /posts HTTP/1.1\r\nHost: localhost\r\nX-User: alice\r\nX-Role: author\r\nContent-Length: 0\r\n\r\n",
+            )
+            .unwrap();
+        let mut response = String::new();
+        client.read_to_string(&mut response).unwrap();
+        server.join().unwrap();
+
+        assert!(response.starts_with("HTTP/1.1 201 Created\r\n"));
+        assert!(response.ends_with("{\"id\":1,\"state\":\"draft\",\"content\":\"\"}"));
+    }
+
+    #[test]
+    fn rejects_oversized_bodies() {
+        let raw = "POST /posts/1/text HTTP/1.1\r\nContent-Length: 99999999999\r\n\r\n";
+        let error = read_request(&mut raw.as_bytes()).err().unwrap();
+        assert!(matches!(error, RequestError::TooLarge(99999999999)));
+        assert_eq!(413, error.response().status);
+    }
+
+    #[test]
+    fn rejects_oversized_headers() {
+        let endless = format!("GET /posts HTTP/1.1\r\nX-Junk: {}", "a".repeat(MAX_HEADER));
+        let error = read_request(&mut endless.as_bytes()).err().unwrap();
+        assert!(matches!(error, RequestError::HeadersTooLarge));
+        assert_eq!(431, error.response().status);
+
+        let many = format!("GET /posts HTTP/1.1\r\n{}\r\n", "X-A: b\r\n".repeat(2000));
+        assert!(matches!(
+            read_request(&mut many.as_bytes()),
+            Err(RequestError::HeadersTooLarge)
+        ));
+    }
+
+    #[test]
+    fn reads_stop_at_the_deadline() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let _client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let (stream, _) = listener.accept().unwrap();
+        let mut reader = Deadline {
+            stream,
+            until: Instant::now(),
+        };
+        let error = reader.read(&mut [0; 16]).unwrap_err();
+        assert_eq!(io::ErrorKind::TimedOut, error.kind());
+    }
+}
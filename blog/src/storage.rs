@@ -0,0 +1,434 @@
+// 文章的持久化存储，阶段以名称保存，加载时用 Stage::parse 恢复
+use crate::history::{Action, Revision};
+use crate::{Post, Stage};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+#[derive(Debug)]
+pub enum StoreError {
+    Io(io::Error),
+    Corrupt(String),
+    // 更新不存在的文章
+    NotFound(u64),
+}
+
+impl fmt::Display for StoreError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            StoreError::Io(e) => write!(f, "io error: {}", e),
+            StoreError::Corrupt(msg) => write!(f, "corrupt post data: {}", msg),
+            StoreError::NotFound(id) => write!(f, "post {} not found", id),
+        }
+    }
+}
+
+impl std::error::Error for StoreError {}
+
+impl From<io::Error> for StoreError {
+    fn from(e: io::Error) -> Self {
+        StoreError::Io(e)
+    }
+}
+
+pub trait PostRepository {
+    // 保存一篇新文章，返回分配的 id
+    fn insert(&mut self, post: &Post) -> Result<u64, StoreError>;
+    // 覆盖已有的文章，id 不存在时返回 NotFound
+    fn update(&mut self, id: u64, post: &Post) -> Result<(), StoreError>;
+    fn get(&self, id: u64) -> Result<Option<Post>, StoreError>;
+    fn remove(&mut self, id: u64) -> Result<(), StoreError>;
+    fn ids(&self) -> Result<Vec<u64>, StoreError>;
+}
+
+// 编码格式：头部若干行 "key: value"，空行之后是正文
+// 每条修订记录一行 "revision: 时间\t操作\t状态\t操作者\t内容"，字段中的特殊字符会被转义
+pub fn encode(post: &Post) -> String {
+    let mut text = format!("state: {}\n", post.state_name());
+    text.push_str(&format!("author: {}\n", escape(&post.author)));
+    for tag in &post.tags {
+        text.push_str(&format!("tag: {}\n", escape(tag)));
+    }
+    if let Some(publish_at) = post.publish_at() {
+        text.push_str(&format!("publish_at: {}\n", publish_at));
+    }
+    for rev in post.history() {
+        text.push_str(&format!(
+            "revision: {}\t{}\t{}\t{}\t{}\n",
+            rev.timestamp,
+            rev.action.as_str(),
+            rev.state,
+            escape(&rev.actor),
+            escape(&rev.content)
+        ));
+    }
+    text.push('\n');
+    text.push_str(&post.content);
+    text
+}
+
+pub fn decode(text: &str) -> Result<Post, StoreError> {
+    let (header, content) = match text.split_once("\n\n") {
+        Some(parts) => parts,
+        None => return Err(StoreError::Corrupt(String::from("missing header"))),
+    };
+    let mut state_name = None;
+    let mut publish_at = None;
+    let mut author = String::new();
+    let mut tags = Vec::new();
+    let mut history = Vec::new();
+    for line in header.lines() {
+        if let Some((key, value)) = line.split_once(": ") {
+            match key {
+                "state" => state_name = Some(value),
+                "author" => author = unescape(value),
+                "tag" => tags.push(unescape(value)),
+                "publish_at" => match value.parse() {
+                    Ok(t) => publish_at = Some(t),
+                    Err(_) => {
+                        return Err(StoreError::Corrupt(format!("bad publish_at `{}`", value)))
+                    }
+                },
+                "revision" => history.push(decode_revision(value)?),
+                _ => {}
+            }
+        }
+    }
+    let name = match state_name {
+        Some(name) => name,
+        None => return Err(StoreError::Corrupt(String::from("missing state"))),
+    };
+    let stage = match Stage::parse(name) {
+        Some(stage) => stage,
+        None => return Err(StoreError::Corrupt(format!("unknown state `{}`", name))),
+    };
+    // 定时发布状态必须带有发布时间，其他状态忽略它
+    match stage {
+        Stage::Scheduled if publish_at.is_none() => {
+            return Err(StoreError::Corrupt(String::from(
+                "scheduled post without publish_at",
+            )))
+        }
+        Stage::Scheduled => {}
+        _ => publish_at = None,
+    }
+    let mut post = Post::with_stage(stage, publish_at, String::from(content));
+    post.history = history;
+    post.author = author;
+    post.tags = tags;
+    Ok(post)
+}
+
+fn decode_revision(value: &str) -> Result<Revision, StoreError> {
+    let corrupt = || StoreError::Corrupt(format!("bad revision `{}`", value));
+    let fields: Vec<&str> = value.split('\t').collect();
+    if fields.len() != 5 {
+        return Err(corrupt());
+    }
+    Ok(Revision {
+        timestamp: fields[0].parse().map_err(|_| corrupt())?,
+        action: Action::parse(fields[1]).ok_or_else(corrupt)?,
+        state: String::from(fields[2]),
+        actor: unescape(fields[3]),
+        content: unescape(fields[4]),
+    })
+}
+
+fn escape(s: &str) -> String {
+    let mut out = String::new();
+    for c in s.chars() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\t' => out.push_str("\\t"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+fn unescape(s: &str) -> String {
+    let mut out = String::new();
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => out.push('\n'),
+            Some('t') => out.push('\t'),
+            Some(other) => out.push(other),
+            None => out.push('\\'),
+        }
+    }
+    out
+}
+
+// 内存实现，保存与文件相同的编码文本，两种实现的行为保持一致：
+// id 从 1 开始递增，删除后也不会重复使用
+#[derive(Default)]
+pub struct MemoryRepository {
+    posts: BTreeMap<u64, String>,
+    // 最近分配的 id
+    last_id: u64,
+}
+
+impl MemoryRepository {
+    pub fn new() -> MemoryRepository {
+        MemoryRepository::default()
+    }
+}
+
+impl PostRepository for MemoryRepository {
+    fn insert(&mut self, post: &Post) -> Result<u64, StoreError> {
+        self.last_id += 1;
+        self.posts.insert(self.last_id, encode(post));
+        Ok(self.last_id)
+    }
+    fn update(&mut self, id: u64, post: &Post) -> Result<(), StoreError> {
+        match self.posts.get_mut(&id) {
+            Some(text) => {
+                *text = encode(post);
+                Ok(())
+            }
+            None => Err(StoreError::NotFound(id)),
+        }
+    }
+    fn get(&self, id: u64) -> Result<Option<Post>, StoreError> {
+        match self.posts.get(&id) {
+            Some(text) => decode(text).map(Some),
+            None => Ok(None),
+        }
+    }
+    fn remove(&mut self, id: u64) -> Result<(), StoreError> {
+        self.posts.remove(&id);
+        Ok(())
+    }
+    fn ids(&self) -> Result<Vec<u64>, StoreError> {
+        Ok(self.posts.keys().copied().collect())
+    }
+}
+
+// 文件实现：每篇文章一个 post-<id>.txt，index 文件记录所有 id，
+// last_id 文件记录最近分配的 id
+pub struct FileRepository {
+    dir: PathBuf,
+}
+
+impl FileRepository {
+    pub fn open(dir: impl Into<PathBuf>) -> Result<FileRepository, StoreError> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+        Ok(FileRepository { dir })
+    }
+
+    fn post_path(&self, id: u64) -> PathBuf {
+        self.dir.join(format!("post-{}.txt", id))
+    }
+
+    fn index_path(&self) -> PathBuf {
+        self.dir.join("index")
+    }
+
+    fn last_id_path(&self) -> PathBuf {
+        self.dir.join("last_id")
+    }
+
+    // 旧目录没有 last_id 文件时退回到现有的最大 id
+    fn last_id(&self, ids: &[u64]) -> Result<u64, StoreError> {
+        let max = ids.iter().copied().max().unwrap_or(0);
+        match fs::read_to_string(self.last_id_path()) {
+            Ok(text) => match text.trim().parse::<u64>() {
+                Ok(last) => Ok(last.max(max)),
+                Err(_) => Err(StoreError::Corrupt(format!(
+                    "bad last_id `{}`",
+                    text.trim()
+                ))),
+            },
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(max),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn write_index(&self, ids: &[u64]) -> Result<(), StoreError> {
+        let mut text = String::new();
+        for id in ids {
+            text.push_str(&format!("{}\n", id));
+        }
+        fs::write(self.index_path(), text)?;
+        Ok(())
+    }
+}
+
+impl PostRepository for FileRepository {
+    fn insert(&mut self, post: &Post) -> Result<u64, StoreError> {
+        let mut ids = self.ids()?;
+        let id = self.last_id(&ids)? + 1;
+        // 先记下 id，即使后面失败也不会再分配同一个 id
+        fs::write(self.last_id_path(), format!("{}\n", id))?;
+        fs::write(self.post_path(id), encode(post))?;
+        ids.push(id);
+        self.write_index(&ids)?;
+        Ok(id)
+    }
+    fn update(&mut self, id: u64, post: &Post) -> Result<(), StoreError> {
+        if !self.ids()?.contains(&id) {
+            return Err(StoreError::NotFound(id));
+        }
+        fs::write(self.post_path(id), encode(post))?;
+        Ok(())
+    }
+    fn get(&self, id: u64) -> Result<Option<Post>, StoreError> {
+        match fs::read_to_string(self.post_path(id)) {
+            Ok(text) => decode(&text).map(Some),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+    fn remove(&mut self, id: u64) -> Result<(), StoreError> {
+        let ids: Vec<u64> = self.ids()?.into_iter().filter(|i| *i != id).collect();
+        self.write_index(&ids)?;
+        match fs::remove_file(self.post_path(id)) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+    fn ids(&self) -> Result<Vec<u64>, StoreError> {
+        let text = match fs::read_to_string(self.index_path()) {
+            Ok(text) => text,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+        let mut ids = Vec::new();
+        for line in text.lines().filter(|l| !l.trim().is_empty()) {
+            match line.trim().parse() {
+                Ok(id) => ids.push(id),
+                Err(_) => return Err(StoreError::Corrupt(format!("bad index entry `{}`", line))),
+            }
+        }
+        Ok(ids)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::{Role, User};
+
+    fn alice() -> User {
+        User::new("alice", Role::Author)
+    }
+
+    fn bob() -> User {
+        User::new("bob", Role::Reviewer)
+    }
+
+    fn published_post() -> Post {
+        let mut post = Post::new(&alice());
+        post.add_text(&alice(), "line one\n\nline two").unwrap();
+        post.request_review(&alice()).unwrap();
+        post.approve(&bob()).unwrap();
+        post
+    }
+
+    #[test]
+    fn memory_round_trip() {
+        let mut repo = MemoryRepository::new();
+        let id = repo.insert(&published_post()).unwrap();
+        let post = repo.get(id).unwrap().unwrap();
+        assert_eq!("published", post.state_name());
+        assert_eq!("line one\n\nline two", post.content());
+        assert_eq!(vec![id], repo.ids().unwrap());
+    }
+
+    #[test]
+    fn scheduled_post_keeps_publish_time() {
+        let mut post = Post::new(&alice());
+        post.request_review(&alice()).unwrap();
+        post.schedule(&bob(), 1_700_000_000).unwrap();
+        let loaded = decode(&encode(&post)).unwrap();
+        assert_eq!("scheduled", loaded.state_name());
+        assert_eq!(Some(1_700_000_000), loaded.publish_at());
+        assert!(decode("state: scheduled\n\n").is_err());
+    }
+
+    #[test]
+    fn history_survives_round_trip() {
+        let author = User::new("tab\tand\\slash", Role::Author);
+        let mut post = Post::new(&author);
+        post.add_text(&author, "a\nb").unwrap();
+        post.add_tag("rust");
+        post.add_tag("rust");
+        let loaded = decode(&encode(&post)).unwrap();
+        assert_eq!(post.history(), loaded.history());
+        assert_eq!("tab\tand\\slash", loaded.author());
+        assert_eq!(vec![String::from("rust")], loaded.tags());
+    }
+
+    #[test]
+    fn file_round_trip() {
+        let dir = std::env::temp_dir().join(format!("blog-store-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let mut repo = FileRepository::open(&dir).unwrap();
+
+        let draft = repo.insert(&Post::new(&alice())).unwrap();
+        let published = repo.insert(&published_post()).unwrap();
+        assert_eq!(vec![draft, published], repo.ids().unwrap());
+
+        // 重新打开目录，模拟重启
+        let mut repo = FileRepository::open(&dir).unwrap();
+        assert_eq!("draft", repo.get(draft).unwrap().unwrap().state_name());
+        assert_eq!(
+            "line one\n\nline two",
+            repo.get(published).unwrap().unwrap().content()
+        );
+
+        repo.remove(draft).unwrap();
+        assert!(repo.get(draft).unwrap().is_none());
+        assert_eq!(vec![published], repo.ids().unwrap());
+
+        // 删除最大的 id 后重新打开，新文章也不会重用它
+        repo.remove(published).unwrap();
+        let mut repo = FileRepository::open(&dir).unwrap();
+        assert_eq!(published + 1, repo.insert(&Post::new(&alice())).unwrap());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn ids_are_not_reused_and_update_needs_existing_post() {
+        let dir = std::env::temp_dir().join(format!("blog-store-ids-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let mut file = FileRepository::open(&dir).unwrap();
+        let mut memory = MemoryRepository::new();
+        let repos: [&mut dyn PostRepository; 2] = [&mut memory, &mut file];
+        for repo in repos {
+            let first = repo.insert(&Post::new(&alice())).unwrap();
+            let second = repo.insert(&Post::new(&alice())).unwrap();
+            repo.remove(second).unwrap();
+            assert_eq!(second + 1, repo.insert(&Post::new(&alice())).unwrap());
+            assert!(matches!(
+                repo.update(second, &published_post()),
+                Err(StoreError::NotFound(id)) if id == second
+            ));
+            assert!(repo.get(second).unwrap().is_none());
+            repo.update(first, &published_post()).unwrap();
+            assert_eq!("published", repo.get(first).unwrap().unwrap().state_name());
+        }
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn decode_rejects_unknown_state() {
+        assert!(decode("state: archived\n\ntext").is_err());
+        assert_eq!(
+            "corrupt post data: scheduled post without publish_at",
+            decode("state: scheduled\n\ntext")
+                .err()
+                .unwrap()
+                .to_string()
+        );
+    }
+}
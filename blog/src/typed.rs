@@ -0,0 +1,360 @@
+// 类型状态实现：每个状态是一个独立的类型，非法的状态转换在编译期就会报错
+use crate::auth::{TransitionError, User};
+use crate::{Post, Stage};
+use std::fmt;
+
+// 每个类型内部都是一篇处于对应阶段的 Post（装箱后移动更便宜），转换时作者、标签和审计记录随之保留
+pub struct DraftPost {
+    post: Box<Post>,
+}
+
+pub struct PendingReviewPost {
+    post: Box<Post>,
+}
+
+pub struct ScheduledPost {
+    post: Box<Post>,
+}
+
+pub struct PublishedPost {
+    post: Box<Post>,
+}
+
+// 转换失败时带回原来的文章，调用方可以换个用户重试或者继续编辑
+pub struct Failed<P> {
+    pub post: P,
+    pub error: TransitionError,
+}
+
+// 文章本身没有实现 Debug，只显示错误
+impl<P> fmt::Debug for Failed<P> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Failed")
+            .field("error", &self.error)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<P> fmt::Display for Failed<P> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.error.fmt(f)
+    }
+}
+
+impl<P> std::error::Error for Failed<P> {}
+
+// 不需要文章时可以用 ? 直接转成 TransitionError
+impl<P> From<Failed<P>> for TransitionError {
+    fn from(failed: Failed<P>) -> Self {
+        failed.error
+    }
+}
+
+impl DraftPost {
+    pub fn new(author: &User) -> DraftPost {
+        DraftPost {
+            post: Box::new(Post::new(author)),
+        }
+    }
+    pub fn add_text(&mut self, actor: &User, text: &str) -> Result<(), TransitionError> {
+        self.post.add_text(actor, text)
+    }
+    pub fn add_tag(&mut self, tag: &str) {
+        self.post.add_tag(tag);
+    }
+    pub fn request_review(mut self, actor: &User) -> Result<PendingReviewPost, Failed<DraftPost>> {
+        match self.post.request_review(actor) {
+            Ok(()) => Ok(PendingReviewPost { post: self.post }),
+            Err(error) => Err(Failed { post: self, error }),
+        }
+    }
+}
+
+// 权限检查与 Post 相同；转换失败时错误里带回原来的文章，和 tick 一样不会丢失
+impl PendingReviewPost {
+    pub fn approve(mut self, actor: &User) -> Result<PublishedPost, Failed<PendingReviewPost>> {
+        match self.post.approve(actor) {
+            Ok(()) => Ok(PublishedPost { post: self.post }),
+            Err(error) => Err(Failed { post: self, error }),
+        }
+    }
+    pub fn reject(mut self, actor: &User) -> Result<DraftPost, Failed<PendingReviewPost>> {
+        match self.post.reject(actor) {
+            Ok(()) => Ok(DraftPost { post: self.post }),
+            Err(error) => Err(Failed { post: self, error }),
+        }
+    }
+    pub fn schedule(
+        mut self,
+        actor: &User,
+        publish_at: u64,
+    ) -> Result<ScheduledPost, Failed<PendingReviewPost>> {
+        match self.post.schedule(actor, publish_at) {
+            Ok(()) => Ok(ScheduledPost { post: self.post }),
+            Err(error) => Err(Failed { post: self, error }),
+        }
+    }
+}
+
+impl ScheduledPost {
+    pub fn publish_at(&self) -> u64 {
+        self.post
+            .publish_at()
+            .expect("scheduled posts have a publish time")
+    }
+    // 未到发布时间时原样返回
+    pub fn tick(mut self, now: u64) -> Result<PublishedPost, ScheduledPost> {
+        self.post.tick(now);
+        match self.post.stage() {
+            Stage::Published => Ok(PublishedPost { post: self.post }),
+            _ => Err(self),
+        }
+    }
+    pub fn unpublish(mut self, actor: &User) -> Result<DraftPost, Failed<ScheduledPost>> {
+        match self.post.unpublish(actor) {
+            Ok(()) => Ok(DraftPost { post: self.post }),
+            Err(error) => Err(Failed { post: self, error }),
+        }
+    }
+}
+
+impl PublishedPost {
+    pub fn content(&self) -> &str {
+        self.post.content()
+    }
+    pub fn unpublish(mut self, actor: &User) -> Result<DraftPost, Failed<PublishedPost>> {
+        match self.post.unpublish(actor) {
+            Ok(()) => Ok(DraftPost { post: self.post }),
+            Err(error) => Err(Failed { post: self, error }),
+        }
+    }
+}
+
+// 作者、标签和审计记录通过内部的 Post 读取
+impl AsRef<Post> for DraftPost {
+    fn as_ref(&self) -> &Post {
+        &self.post
+    }
+}
+
+impl AsRef<Post> for PendingReviewPost {
+    fn as_ref(&self) -> &Post {
+        &self.post
+    }
+}
+
+impl AsRef<Post> for ScheduledPost {
+    fn as_ref(&self) -> &Post {
+        &self.post
+    }
+}
+
+impl AsRef<Post> for PublishedPost {
+    fn as_ref(&self) -> &Post {
+        &self.post
+    }
+}
+
+// 从运行时状态机的 Post 转换得到的类型状态，具体状态只有运行时才知道
+pub enum TypedPost {
+    Draft(DraftPost),
+    PendingReview(PendingReviewPost),
+    Scheduled(ScheduledPost),
+    Published(PublishedPost),
+}
+
+impl From<Post> for TypedPost {
+    fn from(post: Post) -> Self {
+        let stage = post.stage();
+        let post = Box::new(post);
+        match stage {
+            Stage::Draft => TypedPost::Draft(DraftPost { post }),
+            Stage::PendingReview => TypedPost::PendingReview(PendingReviewPost { post }),
+            Stage::Scheduled => TypedPost::Scheduled(ScheduledPost { post }),
+            Stage::Published => TypedPost::Published(PublishedPost { post }),
+        }
+    }
+}
+
+impl From<TypedPost> for Post {
+    fn from(post: TypedPost) -> Self {
+        match post {
+            TypedPost::Draft(p) => p.into(),
+            TypedPost::PendingReview(p) => p.into(),
+            TypedPost::Scheduled(p) => p.into(),
+            TypedPost::Published(p) => p.into(),
+        }
+    }
+}
+
+impl From<DraftPost> for Post {
+    fn from(post: DraftPost) -> Self {
+        *post.post
+    }
+}
+
+impl From<PendingReviewPost> for Post {
+    fn from(post: PendingReviewPost) -> Self {
+        *post.post
+    }
+}
+
+impl From<ScheduledPost> for Post {
+    fn from(post: ScheduledPost) -> Self {
+        *post.post
+    }
+}
+
+impl From<PublishedPost> for Post {
+    fn from(post: PublishedPost) -> Self {
+        *post.post
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::Role;
+    use crate::history::Action;
+
+    fn alice() -> User {
+        User::new("alice", Role::Author)
+    }
+
+    fn bob() -> User {
+        User::new("bob", Role::Reviewer)
+    }
+
+    fn pending() -> PendingReviewPost {
+        let mut post = DraftPost::new(&alice());
+        post.add_text(&alice(), "hello").unwrap();
+        post.request_review(&alice()).unwrap()
+    }
+
+    #[test]
+    fn typed_workflow() {
+        let mut post = DraftPost::new(&alice());
+        post.add_text(&alice(), "I ate a salad for lunch today")
+            .unwrap();
+        let post = post.request_review(&alice()).unwrap();
+        let post = post.approve(&bob()).unwrap();
+        assert_eq!("I ate a salad for lunch today", post.content());
+    }
+
+    #[test]
+    fn typed_transitions_check_roles() {
+        // 作者不能审批，编辑也不能审批自己的文章
+        assert_eq!(
+            Some(TransitionError::Forbidden {
+                action: Action::Approve,
+                role: Role::Author
+            }),
+            pending().approve(&alice()).err().map(|f| f.error)
+        );
+        let editor = User::new("alice", Role::Editor);
+        assert_eq!(
+            Some(TransitionError::SelfApproval),
+            pending().approve(&editor).err().map(|f| f.error)
+        );
+        assert_eq!(
+            Some(TransitionError::SelfApproval),
+            pending().schedule(&editor, 10).err().map(|f| f.error)
+        );
+        let mut other = DraftPost::new(&bob());
+        assert!(other.add_text(&alice(), "not mine").is_err());
+
+        let published = pending().approve(&bob()).unwrap();
+        assert_eq!(
+            Some(TransitionError::Forbidden {
+                action: Action::Unpublish,
+                role: Role::Reviewer
+            }),
+            published.unpublish(&bob()).err().map(|f| f.error)
+        );
+        let carol = User::new("carol", Role::Editor);
+        assert!(pending().approve(&bob()).unwrap().unpublish(&carol).is_ok());
+    }
+
+    #[test]
+    fn failed_transitions_return_the_post() {
+        let mut draft = DraftPost::new(&alice());
+        draft.add_text(&alice(), "hello").unwrap();
+        draft.add_tag("rust");
+        let pending = draft.request_review(&alice()).unwrap();
+        let failed = match pending.approve(&alice()) {
+            Ok(_) => panic!("authors may not approve"),
+            Err(failed) => failed,
+        };
+        assert_eq!("role author may not approve", failed.to_string());
+        // 换一个审阅者重试，标签和审计记录都还在
+        let published = failed.post.approve(&bob()).unwrap();
+        assert_eq!("hello", published.content());
+        assert_eq!(vec![String::from("rust")], published.as_ref().tags());
+        assert_eq!(4, published.as_ref().history().len());
+    }
+
+    #[test]
+    fn convert_between_representations() {
+        let mut post: Post = pending().into();
+        assert_eq!("", post.content());
+        // 转换后作者信息保留，仍然不能自己审批
+        assert!(post.approve(&User::new("alice", Role::Editor)).is_err());
+        post.approve(&bob()).unwrap();
+        assert_eq!("hello", post.content());
+
+        match TypedPost::from(post) {
+            TypedPost::Published(p) => assert_eq!("hello", p.content()),
+            _ => panic!("expected a published post"),
+        }
+    }
+
+    #[test]
+    fn round_trip_keeps_history_and_tags() {
+        let mut draft = DraftPost::new(&alice());
+        draft.add_text(&alice(), "hello").unwrap();
+        draft.add_tag("rust");
+        let published = draft
+            .request_review(&alice())
+            .unwrap()
+            .approve(&bob())
+            .unwrap();
+        let actions: Vec<Action> = published
+            .as_ref()
+            .history()
+            .iter()
+            .map(|r| r.action)
+            .collect();
+        assert_eq!(
+            vec![
+                Action::Create,
+                Action::AddText,
+                Action::RequestReview,
+                Action::Approve
+            ],
+            actions
+        );
+
+        let post: Post = TypedPost::from(Post::from(published)).into();
+        assert_eq!("alice", post.author());
+        assert_eq!(vec![String::from("rust")], post.tags());
+        assert_eq!(4, post.history().len());
+        assert_eq!("bob", post.history()[3].actor);
+        // 导出依赖的发布时间来自审计记录
+        assert!(post.published_at().is_some());
+    }
+
+    #[test]
+    fn typed_schedule() {
+        let post = pending().schedule(&bob(), 10).unwrap();
+        let post = match post.tick(5) {
+            Ok(_) => panic!("published too early"),
+            Err(post) => post,
+        };
+        let post: Post = post.into();
+        assert_eq!(Some(10), post.publish_at());
+        match TypedPost::from(post) {
+            TypedPost::Scheduled(p) => assert!(p.tick(10).is_ok()),
+            _ => panic!("expected a scheduled post"),
+        }
+    }
+}
@@ -0,0 +1,74 @@
+// 绘制目标：组件通过 Canvas 输出，不关心具体是终端还是其他后端
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Rect {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Rect {
+    pub fn new(x: u32, y: u32, width: u32, height: u32) -> Rect {
+        Rect {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    pub fn right(&self) -> u32 {
+        self.x + self.width
+    }
+
+    pub fn bottom(&self) -> u32 {
+        self.y + self.height
+    }
+
+    pub fn contains(&self, x: u32, y: u32) -> bool {
+        x >= self.x && x < self.right() && y >= self.y && y < self.bottom()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Color {
+    // 使用后端的默认颜色
+    #[default]
+    Default,
+    Black,
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+    White,
+    Rgb(u8, u8, u8),
+}
+
+impl Color {
+    // 对应的 RGB 值，Default 由各后端自己决定
+    pub fn rgb(self) -> Option<(u8, u8, u8)> {
+        match self {
+            Color::Default => None,
+            Color::Black => Some((0, 0, 0)),
+            Color::Red => Some((205, 49, 49)),
+            Color::Green => Some((13, 188, 121)),
+            Color::Yellow => Some((229, 229, 16)),
+            Color::Blue => Some((36, 114, 200)),
+            Color::Magenta => Some((188, 63, 188)),
+            Color::Cyan => Some((17, 168, 205)),
+            Color::White => Some((229, 229, 229)),
+            Color::Rgb(r, g, b) => Some((r, g, b)),
+        }
+    }
+}
+
+pub trait Canvas {
+    fn size(&self) -> (u32, u32);
+    fn fill_rect(&mut self, rect: Rect, color: Color);
+    // 画矩形边框
+    fn draw_box(&mut self, rect: Rect, color: Color);
+    // 从 (x, y) 开始写一行文字，不改变背景
+    fn draw_text(&mut self, x: u32, y: u32, text: &str, color: Color);
+}
@@ -0,0 +1,49 @@
+// 输入事件和可交互组件
+use crate::canvas::Rect;
+use crate::Draw;
+use std::any::Any;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Key {
+    Char(char),
+    Enter,
+    Tab,
+    // Shift + Tab
+    BackTab,
+    Up,
+    Down,
+    Left,
+    Right,
+    Backspace,
+    Delete,
+    Home,
+    End,
+    Escape,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Event {
+    Key(Key),
+    // 坐标和 Canvas 使用同一单位
+    Click { x: u32, y: u32 },
+    Resize { width: u32, height: u32 },
+    // 定时器，用于动画等
+    Tick,
+}
+
+// Any 让组件树可以按具体类型取回组件
+pub trait Widget: Draw + Any {
+    // area 为组件布局后的矩形；返回 true 表示事件已处理
+    fn handle_event(&mut self, _event: &Event, _area: Rect) -> bool {
+        false
+    }
+    // 能否通过 Tab 或点击获得焦点
+    fn focusable(&self) -> bool {
+        false
+    }
+    fn set_focused(&mut self, _focused: bool) {}
+    // 禁用的组件不能获得焦点，也不接收事件
+    fn disabled(&self) -> bool {
+        false
+    }
+}
@@ -0,0 +1,343 @@
+// 布局：根据组件的 size() 计算每个组件的矩形
+// 布局树的叶子用下标引用组件，Screen 由组件树生成布局
+use crate::canvas::Rect;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Align {
+    #[default]
+    Start,
+    Center,
+    End,
+    // 占满分配到的空间（受 max 限制）
+    Stretch,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Insets {
+    pub top: u32,
+    pub right: u32,
+    pub bottom: u32,
+    pub left: u32,
+}
+
+impl Insets {
+    pub fn all(n: u32) -> Insets {
+        Insets {
+            top: n,
+            right: n,
+            bottom: n,
+            left: n,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Vertical,
+    Horizontal,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Kind {
+    Item(usize),
+    Stack(Direction, Vec<Layout>),
+    // 按列数从左到右、从上到下排列
+    Grid(usize, Vec<Layout>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Layout {
+    kind: Kind,
+    padding: Insets,
+    spacing: u32,
+    align: (Align, Align),
+    min: (u32, u32),
+    max: (u32, u32),
+}
+
+impl Layout {
+    fn with(kind: Kind) -> Layout {
+        Layout {
+            kind,
+            padding: Insets::default(),
+            spacing: 0,
+            align: (Align::Start, Align::Start),
+            min: (0, 0),
+            max: (u32::MAX, u32::MAX),
+        }
+    }
+
+    // 第 index 个组件
+    pub fn item(index: usize) -> Layout {
+        Layout::with(Kind::Item(index))
+    }
+
+    pub fn vertical(children: Vec<Layout>) -> Layout {
+        Layout::with(Kind::Stack(Direction::Vertical, children))
+    }
+
+    pub fn horizontal(children: Vec<Layout>) -> Layout {
+        Layout::with(Kind::Stack(Direction::Horizontal, children))
+    }
+
+    pub fn grid(columns: usize, children: Vec<Layout>) -> Layout {
+        Layout::with(Kind::Grid(columns.max(1), children))
+    }
+
+    pub fn padding(mut self, padding: Insets) -> Layout {
+        self.padding = padding;
+        self
+    }
+
+    // 子元素之间的间距
+    pub fn spacing(mut self, spacing: u32) -> Layout {
+        self.spacing = spacing;
+        self
+    }
+
+    // 在父布局分配的空间中水平、垂直方向的对齐方式
+    pub fn align(mut self, horizontal: Align, vertical: Align) -> Layout {
+        self.align = (horizontal, vertical);
+        self
+    }
+
+    pub fn min(mut self, width: u32, height: u32) -> Layout {
+        self.min = (width, height);
+        self
+    }
+
+    pub fn max(mut self, width: u32, height: u32) -> Layout {
+        self.max = (width, height);
+        self
+    }
+
+    pub fn is_container(&self) -> bool {
+        !matches!(self.kind, Kind::Item(_))
+    }
+
+    // 保留属性，改为引用第 index 个组件；组件树用它生成叶子的布局
+    pub(crate) fn to_item(&self, index: usize) -> Layout {
+        self.with_kind(Kind::Item(index))
+    }
+
+    // 保留属性和排列方式，换成新的子元素；不是容器时按纵向排列
+    pub(crate) fn to_container(&self, children: Vec<Layout>) -> Layout {
+        let kind = match &self.kind {
+            Kind::Grid(columns, _) => Kind::Grid(*columns, children),
+            Kind::Stack(direction, _) => Kind::Stack(*direction, children),
+            Kind::Item(_) => Kind::Stack(Direction::Vertical, children),
+        };
+        self.with_kind(kind)
+    }
+
+    fn with_kind(&self, kind: Kind) -> Layout {
+        Layout {
+            kind,
+            padding: self.padding,
+            spacing: self.spacing,
+            align: self.align,
+            min: self.min,
+            max: self.max,
+        }
+    }
+
+    fn clamp(&self, (width, height): (u32, u32)) -> (u32, u32) {
+        (
+            width.min(self.max.0).max(self.min.0),
+            height.min(self.max.1).max(self.min.1),
+        )
+    }
+
+    // 期望尺寸，sizes 为各组件的 size()；不存在的组件当作 0
+    pub fn measure(&self, sizes: &[(u32, u32)]) -> (u32, u32) {
+        let (width, height) = match &self.kind {
+            Kind::Item(index) => sizes.get(*index).copied().unwrap_or((0, 0)),
+            Kind::Stack(direction, children) => {
+                let gaps = self.spacing * children.len().saturating_sub(1) as u32;
+                let measured = children.iter().map(|c| c.measure(sizes));
+                match direction {
+                    Direction::Vertical => {
+                        measured.fold((0, gaps), |(w, h), (cw, ch)| (w.max(cw), h + ch))
+                    }
+                    Direction::Horizontal => {
+                        measured.fold((gaps, 0), |(w, h), (cw, ch)| (w + cw, h.max(ch)))
+                    }
+                }
+            }
+            Kind::Grid(columns, children) => {
+                let (widths, heights) = self.tracks(*columns, children, sizes);
+                (
+                    widths.iter().sum::<u32>() + self.spacing * gaps(widths.len()),
+                    heights.iter().sum::<u32>() + self.spacing * gaps(heights.len()),
+                )
+            }
+        };
+        self.clamp((
+            width + self.padding.left + self.padding.right,
+            height + self.padding.top + self.padding.bottom,
+        ))
+    }
+
+    // 网格每列的宽度和每行的高度
+    fn tracks(
+        &self,
+        columns: usize,
+        children: &[Layout],
+        sizes: &[(u32, u32)],
+    ) -> (Vec<u32>, Vec<u32>) {
+        let mut widths = vec![0; columns.min(children.len())];
+        let mut heights = vec![0; children.len().div_ceil(columns)];
+        for (i, child) in children.iter().enumerate() {
+            let (w, h) = child.measure(sizes);
+            widths[i % columns] = widths[i % columns].max(w);
+            heights[i / columns] = heights[i / columns].max(h);
+        }
+        (widths, heights)
+    }
+
+    // 计算每个组件的矩形，没有出现在布局中的组件为 None
+    pub fn arrange(&self, area: Rect, sizes: &[(u32, u32)]) -> Vec<Option<Rect>> {
+        let mut rects = vec![None; sizes.len()];
+        self.place(area, sizes, &mut rects);
+        rects
+    }
+
+    fn place(&self, slot: Rect, sizes: &[(u32, u32)], rects: &mut [Option<Rect>]) {
+        let natural = self.measure(sizes);
+        let (x, width) = align(self.align.0, slot.x, slot.width, natural.0, self.max.0);
+        let (y, height) = align(self.align.1, slot.y, slot.height, natural.1, self.max.1);
+        let inner = Rect::new(
+            x + self.padding.left,
+            y + self.padding.top,
+            width.saturating_sub(self.padding.left + self.padding.right),
+            height.saturating_sub(self.padding.top + self.padding.bottom),
+        );
+        match &self.kind {
+            Kind::Item(index) => {
+                if let Some(rect) = rects.get_mut(*index) {
+                    *rect = Some(inner);
+                }
+            }
+            Kind::Stack(direction, children) => {
+                let mut offset = 0;
+                for child in children {
+                    let (w, h) = child.measure(sizes);
+                    let slot = match direction {
+                        Direction::Vertical => Rect::new(inner.x, inner.y + offset, inner.width, h),
+                        Direction::Horizontal => {
+                            Rect::new(inner.x + offset, inner.y, w, inner.height)
+                        }
+                    };
+                    child.place(slot, sizes, rects);
+                    offset += self.spacing
+                        + match direction {
+                            Direction::Vertical => h,
+                            Direction::Horizontal => w,
+                        };
+                }
+            }
+            Kind::Grid(columns, children) => {
+                let (widths, heights) = self.tracks(*columns, children, sizes);
+                for (i, child) in children.iter().enumerate() {
+                    let (column, row) = (i % columns, i / columns);
+                    let x: u32 =
+                        widths[..column].iter().sum::<u32>() + self.spacing * column as u32;
+                    let y: u32 = heights[..row].iter().sum::<u32>() + self.spacing * row as u32;
+                    let slot = Rect::new(inner.x + x, inner.y + y, widths[column], heights[row]);
+                    child.place(slot, sizes, rects);
+                }
+            }
+        }
+    }
+}
+
+fn gaps(n: usize) -> u32 {
+    n.saturating_sub(1) as u32
+}
+
+// 在 [start, start + available) 中放置长度为 natural 的元素，返回起点和长度
+fn align(align: Align, start: u32, available: u32, natural: u32, max: u32) -> (u32, u32) {
+    let len = match align {
+        Align::Stretch => available.min(max).max(natural),
+        _ => natural,
+    };
+    let free = available.saturating_sub(len);
+    let offset = match align {
+        Align::Start | Align::Stretch => 0,
+        Align::Center => free / 2,
+        Align::End => free,
+    };
+    (start + offset, len)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stacks_with_spacing_and_padding() {
+        let sizes = [(10, 3), (4, 1), (6, 2)];
+        let layout = Layout::vertical(vec![
+            Layout::item(0),
+            Layout::horizontal(vec![Layout::item(1), Layout::item(2)]).spacing(2),
+        ])
+        .spacing(1)
+        .padding(Insets::all(1));
+        assert_eq!((14, 8), layout.measure(&sizes));
+        assert_eq!(
+            vec![
+                Some(Rect::new(1, 1, 10, 3)),
+                Some(Rect::new(1, 5, 4, 1)),
+                Some(Rect::new(7, 5, 6, 2)),
+            ],
+            layout.arrange(Rect::new(0, 0, 80, 24), &sizes)
+        );
+    }
+
+    #[test]
+    fn aligns_and_constrains() {
+        let sizes = [(10, 1), (10, 1), (10, 1), (50, 1)];
+        let layout = Layout::vertical(vec![
+            Layout::item(0).align(Align::Center, Align::Start),
+            Layout::item(1).align(Align::End, Align::Start),
+            Layout::item(2)
+                .align(Align::Stretch, Align::Start)
+                .max(30, 1),
+            Layout::item(3).max(20, 1).min(0, 2),
+        ])
+        // 根节点占满整个区域，子元素才有对齐的余地
+        .align(Align::Stretch, Align::Stretch);
+        let rects = layout.arrange(Rect::new(0, 0, 40, 10), &sizes);
+        assert_eq!(Some(Rect::new(15, 0, 10, 1)), rects[0]);
+        assert_eq!(Some(Rect::new(30, 1, 10, 1)), rects[1]);
+        assert_eq!(Some(Rect::new(0, 2, 30, 1)), rects[2]);
+        assert_eq!(Some(Rect::new(0, 3, 20, 2)), rects[3]);
+    }
+
+    #[test]
+    fn grid_uses_column_and_row_tracks() {
+        let sizes = [(3, 1), (5, 2), (4, 1), (2, 1), (9, 9)];
+        let layout = Layout::grid(
+            2,
+            vec![
+                Layout::item(0),
+                Layout::item(1),
+                Layout::item(2),
+                Layout::item(3),
+            ],
+        )
+        .spacing(1);
+        assert_eq!((10, 4), layout.measure(&sizes));
+        let rects = layout.arrange(Rect::new(0, 0, 80, 24), &sizes);
+        assert_eq!(
+            vec![
+                Some(Rect::new(0, 0, 3, 1)),
+                Some(Rect::new(5, 0, 5, 2)),
+                Some(Rect::new(0, 3, 4, 1)),
+                Some(Rect::new(5, 3, 2, 1)),
+                None,
+            ],
+            rects
+        );
+    }
+}
@@ -1,31 +1,352 @@
+pub mod canvas;
+pub mod event;
+mod font;
+pub mod layout;
+pub mod markup;
+pub mod raster;
+pub mod svg;
+pub mod terminal;
+pub mod theme;
+pub mod tree;
+pub mod widgets;
+
+pub use canvas::{Canvas, Color, Rect};
+pub use event::{Event, Key, Widget};
+pub use layout::{Align, Insets, Layout};
+pub use markup::MarkupError;
+pub use theme::{State, Style, Theme};
+pub use tree::{WidgetId, WidgetTree};
+pub use widgets::{Checkbox, Label, ListView, ProgressBar, SelectBox, Table, TextInput};
+
 pub trait Draw {
-    fn draw(&self);
+    // 在 area 范围内按 style 绘制自己，style 已经按主题层叠好
+    fn draw(&self, canvas: &mut dyn Canvas, area: Rect, style: &Style);
+    // 期望的宽高
+    fn size(&self) -> (u32, u32);
+    // 组件类型名，导出 SVG 时作为分组的 class，也是主题中的选择器
+    fn kind(&self) -> &'static str {
+        "component"
+    }
+}
+
+// 按布局计算每个组件的矩形；没有指定布局时从上到下依次排列
+fn arrange(layout: Option<&Layout>, sizes: &[(u32, u32)], area: Rect) -> Vec<Option<Rect>> {
+    match layout {
+        Some(layout) => layout.arrange(area, sizes),
+        None => Layout::vertical((0..sizes.len()).map(Layout::item).collect()).arrange(area, sizes),
+    }
+}
+
+fn canvas_area(canvas: &dyn Canvas) -> Rect {
+    let (width, height) = canvas.size();
+    Rect::new(0, 0, width, height)
 }
+
+// 主题为 "screen" 设置了背景色时先铺满整个画面
+fn fill_background(canvas: &mut dyn Canvas, theme: &Theme, area: Rect) {
+    if let Some(bg) = theme.resolve("screen", State::Normal).bg {
+        canvas.fill_rect(area, bg);
+    }
+}
+
+// 画面由组件树组成，修改过的组件在下一次 render 时才重绘
 pub struct Screen {
-    pub components: Vec<Box<dyn Draw>>,
+    tree: WidgetTree,
+    // 获得焦点的组件
+    focus: Option<WidgetId>,
+    theme: Theme,
+    // 上次 render 时的画面区域和布局，变化后需要整屏重绘
+    drawn: Option<(Rect, Vec<(WidgetId, Rect)>)>,
+}
+
+impl Default for Screen {
+    fn default() -> Self {
+        Screen::new()
+    }
 }
 
 impl Screen {
-    pub fn run(&self) {
-        for component in self.components.iter() {
-            component.draw();
+    // 根容器从上到下依次排列子元素
+    pub fn new() -> Screen {
+        Screen::with_layout(Layout::vertical(vec![]))
+    }
+
+    pub fn with_layout(layout: Layout) -> Screen {
+        Screen {
+            tree: WidgetTree::new(layout),
+            focus: None,
+            theme: Theme::dark(),
+            drawn: None,
+        }
+    }
+
+    pub fn tree(&self) -> &WidgetTree {
+        &self.tree
+    }
+
+    // 通过 id 修改组件，例如 tree_mut().get_mut::<Label>(id)，修改的组件会被标记为脏
+    pub fn tree_mut(&mut self) -> &mut WidgetTree {
+        &mut self.tree
+    }
+
+    pub fn root(&self) -> WidgetId {
+        self.tree.root()
+    }
+
+    // parent 不是容器或已被删除时返回 None
+    pub fn add(&mut self, parent: WidgetId, widget: impl Widget) -> Option<WidgetId> {
+        self.tree.add(parent, widget)
+    }
+
+    pub fn add_container(&mut self, parent: WidgetId, layout: Layout) -> Option<WidgetId> {
+        self.tree.add_container(parent, layout)
+    }
+
+    pub fn remove(&mut self, id: WidgetId) -> bool {
+        self.tree.remove(id)
+    }
+
+    // 组件被删除后焦点自动失效
+    pub fn focus(&self) -> Option<WidgetId> {
+        self.focus.filter(|id| self.tree.contains(*id))
+    }
+
+    pub fn theme(&self) -> &Theme {
+        &self.theme
+    }
+
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme;
+        self.invalidate();
+    }
+
+    // 下一次 render 时整屏重绘
+    pub fn invalidate(&mut self) {
+        self.drawn = None;
+    }
+
+    // 叶子组件的矩形，按绘制顺序排列
+    pub fn arrange(&self, area: Rect) -> Vec<(WidgetId, Rect)> {
+        self.tree.arrange(area)
+    }
+
+    // 组件按当前状态得到的样式
+    pub fn style(&self, id: WidgetId) -> Style {
+        let widget = match self.tree.widget(id) {
+            Some(widget) => widget,
+            None => return Style::default(),
+        };
+        let state = if widget.disabled() {
+            State::Disabled
+        } else if self.focus() == Some(id) {
+            State::Focused
+        } else {
+            State::Normal
+        };
+        self.theme.resolve(widget.kind(), state)
+    }
+
+    fn draw_widget(&self, canvas: &mut dyn Canvas, id: WidgetId, rect: Rect) {
+        if let Some(widget) = self.tree.widget(id) {
+            widget.draw(canvas, rect, &self.style(id));
+        }
+    }
+
+    // 画出所有组件，不改变脏标记
+    pub fn run(&self, canvas: &mut dyn Canvas) {
+        let area = canvas_area(canvas);
+        fill_background(canvas, &self.theme, area);
+        for (id, rect) in self.arrange(area) {
+            self.draw_widget(canvas, id, rect);
+        }
+    }
+
+    // 增量重绘：布局没变时只清除并重画脏的子树，否则整屏重绘；
+    // 返回重画的组件数
+    pub fn render(&mut self, canvas: &mut dyn Canvas) -> usize {
+        let area = canvas_area(canvas);
+        let rects = self.arrange(area);
+        let full =
+            self.tree.structure_changed() || self.drawn.as_ref() != Some(&(area, rects.clone()));
+        let bg = self.theme.resolve("screen", State::Normal).bg();
+        if full {
+            canvas.fill_rect(area, bg);
+        }
+        let mut count = 0;
+        for &(id, rect) in &rects {
+            if full || self.tree.is_dirty(id) {
+                if !full {
+                    canvas.fill_rect(rect, bg);
+                }
+                self.draw_widget(canvas, id, rect);
+                count += 1;
+            }
+        }
+        self.tree.clear_dirty();
+        self.drawn = Some((area, rects));
+        count
+    }
+
+    // 导出 SVG 文档，columns、rows 为画面大小
+    pub fn to_svg(&self, columns: u32, rows: u32, stylesheet: Option<&str>) -> String {
+        let rects = self.arrange(Rect::new(0, 0, columns, rows));
+        let items = rects.into_iter().filter_map(|(id, rect)| {
+            let widget = self.tree.widget(id)?;
+            Some((widget as &dyn Draw, Some(rect), self.style(id)))
+        });
+        svg::render(items, columns, rows, stylesheet)
+    }
+
+    fn can_focus(&self, id: WidgetId) -> bool {
+        self.tree
+            .widget(id)
+            .is_some_and(|w| w.focusable() && !w.disabled())
+    }
+
+    // 点击位置上的组件，重叠时后画的在上面
+    pub fn component_at(&self, area: Rect, x: u32, y: u32) -> Option<WidgetId> {
+        self.arrange(area)
+            .into_iter()
+            .rev()
+            .find(|(_, rect)| rect.contains(x, y))
+            .map(|(id, _)| id)
+    }
+
+    // 焦点变化的两个组件都会被标记为脏
+    pub fn set_focus(&mut self, focus: Option<WidgetId>) {
+        if let Some(old) = self.focus.take() {
+            if let Some(widget) = self.tree.widget_mut(old) {
+                widget.set_focused(false);
+            }
+        }
+        self.focus = focus.filter(|&id| self.can_focus(id));
+        if let Some(new) = self.focus {
+            if let Some(widget) = self.tree.widget_mut(new) {
+                widget.set_focused(true);
+            }
+        }
+    }
+
+    // 按树的深度优先顺序切换到下一个（或上一个）可获得焦点的组件，到头后回绕
+    pub fn focus_next(&mut self, backward: bool) {
+        let leaves = self.tree.leaves();
+        let len = leaves.len();
+        if len == 0 {
+            return;
+        }
+        let current = self
+            .focus()
+            .and_then(|id| leaves.iter().position(|l| *l == id));
+        let start = match (current, backward) {
+            (Some(i), _) => i,
+            (None, false) => len - 1,
+            (None, true) => 0,
+        };
+        for step in 1..=len {
+            let i = if backward {
+                (start + len * 2 - step) % len
+            } else {
+                (start + step) % len
+            };
+            if self.can_focus(leaves[i]) {
+                self.set_focus(Some(leaves[i]));
+                return;
+            }
+        }
+    }
+
+    // 交给组件处理，处理了的组件需要重绘
+    fn dispatch(&mut self, id: WidgetId, event: &Event, rect: Rect) -> bool {
+        let handled = match self.tree.widget_mut_clean(id) {
+            Some(widget) => widget.handle_event(event, rect),
+            None => false,
+        };
+        if handled {
+            self.tree.mark_dirty(id);
+        }
+        handled
+    }
+
+    // 分发事件：Tab 切换焦点，按键交给焦点组件，点击交给被点中的组件，
+    // 其他事件广播给所有组件
+    pub fn handle_event(&mut self, event: &Event, area: Rect) -> bool {
+        let rects = self.arrange(area);
+        let rect_of = |id: WidgetId| rects.iter().find(|(i, _)| *i == id).map(|(_, r)| *r);
+        match event {
+            Event::Key(Key::Tab) => {
+                self.focus_next(false);
+                true
+            }
+            Event::Key(Key::BackTab) => {
+                self.focus_next(true);
+                true
+            }
+            Event::Key(_) => match self.focus().and_then(|id| Some((id, rect_of(id)?))) {
+                Some((id, rect)) => self.dispatch(id, event, rect),
+                None => false,
+            },
+            Event::Click { x, y } => match self.component_at(area, *x, *y) {
+                // 禁用的组件不响应点击
+                Some(id) if self.tree.widget(id).is_some_and(|w| w.disabled()) => false,
+                Some(id) => {
+                    if self.can_focus(id) {
+                        self.set_focus(Some(id));
+                    }
+                    match rect_of(id) {
+                        Some(rect) => self.dispatch(id, event, rect),
+                        None => false,
+                    }
+                }
+                None => false,
+            },
+            Event::Resize { .. } | Event::Tick => {
+                let mut handled = false;
+                for &(id, rect) in &rects {
+                    if !self.tree.widget(id).is_some_and(|w| w.disabled()) {
+                        handled |= self.dispatch(id, event, rect);
+                    }
+                }
+                handled
+            }
         }
     }
 }
 // 泛型实现，仅支持一种类型
 pub struct Screen1<T: Draw> {
     pub components: Vec<T>,
+    pub layout: Option<Layout>,
+    pub theme: Theme,
 }
 
 impl<T> Screen1<T>
 where
     T: Draw,
 {
-    pub fn run(&self) {
-        for component in self.components.iter() {
-            component.draw()
+    pub fn arrange(&self, area: Rect) -> Vec<Option<Rect>> {
+        let sizes: Vec<(u32, u32)> = self.components.iter().map(|c| c.size()).collect();
+        arrange(self.layout.as_ref(), &sizes, area)
+    }
+
+    pub fn run(&self, canvas: &mut dyn Canvas) {
+        let area = canvas_area(canvas);
+        fill_background(canvas, &self.theme, area);
+        let rects = self.arrange(area);
+        for (component, rect) in self.components.iter().zip(rects) {
+            if let Some(rect) = rect {
+                let style = self.theme.resolve(component.kind(), State::Normal);
+                component.draw(canvas, rect, &style)
+            }
         }
     }
+
+    pub fn to_svg(&self, columns: u32, rows: u32, stylesheet: Option<&str>) -> String {
+        let rects = self.arrange(Rect::new(0, 0, columns, rows));
+        let items = self.components.iter().zip(rects).map(|(c, rect)| {
+            let style = self.theme.resolve(c.kind(), State::Normal);
+            (c as &dyn Draw, rect, style)
+        });
+        svg::render(items, columns, rows, stylesheet)
+    }
 }
 ///////////////////////////
 
@@ -33,10 +354,195 @@ pub struct Button {
     pub width: u32,
     pub height: u32,
     pub label: String,
+    pub disabled: bool,
+    focused: bool,
+    on_click: Option<Box<dyn FnMut()>>,
+}
+
+impl Button {
+    pub fn new(width: u32, height: u32, label: &str) -> Button {
+        Button {
+            width,
+            height,
+            label: String::from(label),
+            disabled: false,
+            focused: false,
+            on_click: None,
+        }
+    }
+
+    // 点击或获得焦点时按回车、空格触发
+    pub fn on_click(mut self, f: impl FnMut() + 'static) -> Button {
+        self.on_click = Some(Box::new(f));
+        self
+    }
+
+    pub fn click(&mut self) {
+        if let Some(f) = self.on_click.as_mut() {
+            f();
+        }
+    }
 }
 
 impl Draw for Button {
-    fn draw(&self) {
-        // draw something
+    fn draw(&self, canvas: &mut dyn Canvas, area: Rect, style: &Style) {
+        canvas.fill_rect(area, style.bg());
+        canvas.draw_box(area, style.border());
+        // 标签居中
+        let len = self.label.chars().count() as u32;
+        let x = area.x + area.width.saturating_sub(len) / 2;
+        let y = area.y + area.height.saturating_sub(1) / 2;
+        canvas.draw_text(x, y, &self.label, style.fg());
+    }
+
+    fn size(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    fn kind(&self) -> &'static str {
+        "button"
+    }
+}
+
+impl Widget for Button {
+    fn handle_event(&mut self, event: &Event, area: Rect) -> bool {
+        match event {
+            Event::Click { x, y } if area.contains(*x, *y) => {}
+            Event::Key(Key::Enter) | Event::Key(Key::Char(' ')) if self.focused => {}
+            _ => return false,
+        }
+        self.click();
+        true
+    }
+
+    fn focusable(&self) -> bool {
+        true
+    }
+
+    fn set_focused(&mut self, focused: bool) {
+        self.focused = focused;
+    }
+
+    fn disabled(&self) -> bool {
+        self.disabled
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    // 不可交互的占位组件
+    struct Spacer;
+
+    impl Draw for Spacer {
+        fn draw(&self, _canvas: &mut dyn Canvas, _area: Rect, _style: &Style) {}
+        fn size(&self) -> (u32, u32) {
+            (10, 1)
+        }
+    }
+
+    impl Widget for Spacer {}
+
+    fn counting_button(count: &Rc<Cell<u32>>) -> Button {
+        let count = Rc::clone(count);
+        Button::new(10, 3, "OK").on_click(move || count.set(count.get() + 1))
+    }
+
+    #[test]
+    fn tab_cycles_focus_over_focusable_components() {
+        let count = Rc::new(Cell::new(0));
+        let mut screen = Screen::new();
+        let root = screen.root();
+        let first = screen.add(root, counting_button(&count)).unwrap();
+        let row = screen
+            .add_container(root, Layout::horizontal(vec![]))
+            .unwrap();
+        screen.add(row, Spacer);
+        let second = screen.add(row, counting_button(&count)).unwrap();
+        let area = Rect::new(0, 0, 80, 24);
+        screen.handle_event(&Event::Key(Key::Tab), area);
+        assert_eq!(Some(first), screen.focus());
+        screen.handle_event(&Event::Key(Key::Tab), area);
+        assert_eq!(Some(second), screen.focus());
+        screen.handle_event(&Event::Key(Key::Tab), area);
+        assert_eq!(Some(first), screen.focus());
+        screen.handle_event(&Event::Key(Key::BackTab), area);
+        assert_eq!(Some(second), screen.focus());
+
+        assert!(screen.handle_event(&Event::Key(Key::Enter), area));
+        assert_eq!(1, count.get());
+        screen.remove(row);
+        assert_eq!(None, screen.focus());
+    }
+
+    #[test]
+    fn clicks_hit_test_laid_out_rects() {
+        let count = Rc::new(Cell::new(0));
+        let mut screen = Screen::new();
+        let root = screen.root();
+        screen.add(root, Spacer);
+        let button = screen.add(root, counting_button(&count)).unwrap();
+        let area = Rect::new(0, 0, 80, 24);
+        assert_eq!(Some(button), screen.component_at(area, 3, 2));
+        assert!(!screen.handle_event(&Event::Click { x: 3, y: 0 }, area));
+        assert!(screen.handle_event(&Event::Click { x: 3, y: 2 }, area));
+        assert_eq!(1, count.get());
+        assert_eq!(Some(button), screen.focus());
+        assert_eq!(None, screen.component_at(area, 30, 2));
+    }
+
+    #[test]
+    fn disabled_components_are_skipped_and_styled() {
+        let count = Rc::new(Cell::new(0));
+        let mut disabled = counting_button(&count);
+        disabled.disabled = true;
+        let mut screen = Screen::new();
+        let root = screen.root();
+        let first = screen.add(root, disabled).unwrap();
+        let second = screen.add(root, counting_button(&count)).unwrap();
+        let area = Rect::new(0, 0, 80, 24);
+        screen.handle_event(&Event::Key(Key::Tab), area);
+        screen.handle_event(&Event::Key(Key::Tab), area);
+        assert_eq!(Some(second), screen.focus());
+        assert!(!screen.handle_event(&Event::Click { x: 1, y: 1 }, area));
+        assert_eq!(0, count.get());
+
+        assert_eq!(
+            screen.theme().resolve("button", State::Disabled),
+            screen.style(first)
+        );
+        assert_eq!(Color::Yellow, screen.style(second).border());
+        screen.set_theme(Theme::light());
+        assert_eq!(Color::Blue, screen.style(second).border());
+    }
+
+    #[test]
+    fn render_redraws_only_dirty_subtrees() {
+        let mut screen = Screen::new();
+        let root = screen.root();
+        let title = screen.add(root, Label::new("title")).unwrap();
+        let row = screen
+            .add_container(root, Layout::horizontal(vec![]).spacing(1))
+            .unwrap();
+        let ok = screen.add(row, Button::new(4, 3, "OK")).unwrap();
+        screen.add(row, Button::new(4, 3, "No"));
+        let mut terminal = terminal::Terminal::new(20, 5);
+        assert_eq!(3, screen.render(&mut terminal));
+        assert_eq!(0, screen.render(&mut terminal));
+
+        screen.tree_mut().get_mut::<Label>(title).unwrap().text = String::from("other");
+        assert_eq!(1, screen.render(&mut terminal));
+        assert_eq!('o', terminal.cell(0, 0).unwrap().ch);
+        // 焦点变化只影响按钮自己
+        screen.set_focus(Some(ok));
+        assert_eq!(1, screen.render(&mut terminal));
+        screen.tree_mut().mark_dirty(row);
+        assert_eq!(2, screen.render(&mut terminal));
+        // 尺寸变化导致重新布局，整屏重绘
+        screen.tree_mut().get_mut::<Label>(title).unwrap().text = String::from("longer title");
+        assert_eq!(3, screen.render(&mut terminal));
     }
 }
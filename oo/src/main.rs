@@ -1,36 +1,148 @@
-use oo::Draw;
-use oo::{Button, Screen};
+use oo::terminal::Terminal;
+use oo::{Align, Color, Event, Insets, Key, Layout, Rect, WidgetId};
+use oo::{Button, Checkbox, Label, Screen, SelectBox, TextInput, Theme};
+use std::cell::RefCell;
+use std::env;
+use std::io::{self, BufRead, Write};
+use std::process;
+use std::rc::Rc;
 
-struct SelectBox {
-    width: u32,
-    height: u32,
-    options: Vec<String>,
+// 终端默认是行缓冲的，这里每行输入一个命令：
+// tab、backtab、up、down、left、right、enter、type TEXT、bs、click X Y、q
+fn parse_event(line: &str) -> Vec<Event> {
+    let key = |k| vec![Event::Key(k)];
+    if let Some(text) = line.strip_prefix("type ") {
+        return text.chars().map(|c| Event::Key(Key::Char(c))).collect();
+    }
+    let words: Vec<&str> = line.split_whitespace().collect();
+    match words.as_slice() {
+        ["tab"] => key(Key::Tab),
+        ["backtab"] => key(Key::BackTab),
+        ["up"] => key(Key::Up),
+        ["down"] => key(Key::Down),
+        ["left"] => key(Key::Left),
+        ["right"] => key(Key::Right),
+        ["bs"] => key(Key::Backspace),
+        ["enter"] | [] => key(Key::Enter),
+        ["click", x, y] => match (x.parse(), y.parse()) {
+            (Ok(x), Ok(y)) => vec![Event::Click { x, y }],
+            _ => Vec::new(),
+        },
+        _ => Vec::new(),
+    }
 }
 
-impl Draw for SelectBox {
-    fn draw(&self) {
-        // draw something
+// 第一个参数为 dark、light 或主题配置文件，配置文件在深色主题的基础上覆盖
+fn load_theme(arg: Option<String>) -> Theme {
+    match arg.as_deref() {
+        None | Some("dark") => Theme::dark(),
+        Some("light") => Theme::light(),
+        Some(path) => match Theme::load(path) {
+            Ok(theme) => theme.over(&Theme::dark()),
+            Err(err) => {
+                eprintln!("Problem loading theme {}: {}", path, err);
+                process::exit(1);
+            }
+        },
     }
 }
 
-fn main() {
-    let screen = Screen {
-        components: vec![
-            Box::new(SelectBox {
-                width: 75,
-                height: 10,
-                options: vec![
-                    String::from("Yes"),
-                    String::from("Maybe"),
-                    String::from("No"),
-                ],
+// 内置的示例界面，状态栏显示各组件回调的结果
+fn demo_screen(status: &Rc<RefCell<String>>) -> (Screen, WidgetId) {
+    let on_select = Rc::clone(status);
+    let on_toggle = Rc::clone(status);
+    let on_submit = Rc::clone(status);
+    let on_click = Rc::clone(status);
+    let mut screen =
+        Screen::with_layout(Layout::vertical(vec![]).spacing(1).padding(Insets::all(1)));
+    let root = screen.root();
+    screen.add(root, Label::new("Would you like to continue?"));
+    screen.add(
+        root,
+        SelectBox::new(75, 5, &["Yes", "Maybe", "No"])
+            .on_select(move |_, option| *on_select.borrow_mut() = format!("chose {}", option)),
+    );
+    screen.add(
+        root,
+        TextInput::new(40)
+            .on_submit(move |text| *on_submit.borrow_mut() = format!("name: {}", text)),
+    );
+    screen.add(
+        root,
+        Checkbox::new("Remember me")
+            .on_toggle(move |checked| *on_toggle.borrow_mut() = format!("remember: {}", checked)),
+    );
+    let ok = screen
+        .add(
+            root,
+            Button::new(50, 3, "OK").on_click(move || {
+                let text = format!("OK pressed ({})", on_click.borrow());
+                *on_click.borrow_mut() = text;
             }),
-            Box::new(Button {
-                width: 50,
-                height: 10,
-                label: String::from("OK"),
-            }),
-        ],
+        )
+        .expect("root is a container");
+    // 按钮在下方居中，其余组件左对齐
+    screen
+        .tree_mut()
+        .set_layout(ok, Layout::item(0).align(Align::Center, Align::Start));
+    let mut label = Label::new(&status.borrow());
+    label.color = Some(Color::Green);
+    let status_label = screen.add(root, label).expect("root is a container");
+    (screen, status_label)
+}
+
+// 第二个参数为界面描述文件时用它代替内置界面
+fn main() {
+    let theme = load_theme(env::args().nth(1));
+    let status = Rc::new(RefCell::new(String::from("Tab to move focus")));
+    let (mut screen, status_label) = match env::args().nth(2) {
+        Some(path) => match Screen::load(&path) {
+            Ok(screen) => (screen, None),
+            Err(err) => {
+                eprintln!("Problem loading screen {}: {}", path, err);
+                process::exit(1);
+            }
+        },
+        None => {
+            let (screen, label) = demo_screen(&status);
+            (screen, Some(label))
+        }
     };
-    screen.run();
+    screen.set_theme(theme);
+
+    let mut terminal = Terminal::new(80, 22);
+    let area = Rect::new(0, 0, 80, 22);
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+    // 清屏后输出
+    write!(out, "\x1b[2J").unwrap();
+    let mut lines = io::stdin().lock().lines();
+    loop {
+        // 状态文字变了才更新，只有它所在的区域会重绘
+        if let Some(id) = status_label {
+            let tree = screen.tree_mut();
+            if tree
+                .get::<Label>(id)
+                .is_some_and(|l| l.text != *status.borrow())
+            {
+                tree.get_mut::<Label>(id).unwrap().text = status.borrow().clone();
+            }
+        }
+        screen.render(&mut terminal);
+        terminal.flush(&mut out).unwrap();
+        // 输入提示放在画面下方
+        write!(out, "\x1b[23;1H\x1b[2K> ").unwrap();
+        out.flush().unwrap();
+
+        let line = match lines.next() {
+            Some(line) => line.unwrap(),
+            None => break,
+        };
+        if line.trim() == "q" {
+            break;
+        }
+        for event in parse_event(&line) {
+            screen.handle_event(&event, area);
+        }
+    }
 }
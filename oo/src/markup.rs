@@ -0,0 +1,658 @@
+// 用 S 表达式描述界面，运行时生成组件树，修改界面不需要重新编译：
+//   ; 注释
+//   (vertical :spacing 1 :padding 1
+//     (label "Would you like to continue?")
+//     (select :id choice :width 30 :options ("Yes" "No"))
+//     (horizontal :spacing 2
+//       (button "OK" :id ok :width 8)
+//       (button "Cancel" :disabled true)))
+// 最外层必须是 vertical、horizontal 或 grid 容器，:id 的组件可以用 tree().find 查找
+use crate::layout::{Align, Insets, Layout};
+use crate::theme::parse_color;
+use crate::tree::WidgetId;
+use crate::widgets::{Checkbox, Label, ListView, ProgressBar, SelectBox, Table, TextInput};
+use crate::{Button, Screen, Widget};
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+#[derive(Debug)]
+pub enum MarkupError {
+    Io(io::Error),
+    // 行号和列号从 1 开始
+    Syntax {
+        line: usize,
+        column: usize,
+        message: String,
+    },
+}
+
+impl fmt::Display for MarkupError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MarkupError::Io(e) => write!(f, "io error: {}", e),
+            MarkupError::Syntax {
+                line,
+                column,
+                message,
+            } => write!(f, "line {}, column {}: {}", line, column, message),
+        }
+    }
+}
+
+impl std::error::Error for MarkupError {}
+
+impl From<io::Error> for MarkupError {
+    fn from(e: io::Error) -> Self {
+        MarkupError::Io(e)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Pos {
+    line: usize,
+    column: usize,
+}
+
+impl Pos {
+    fn error(self, message: String) -> MarkupError {
+        MarkupError::Syntax {
+            line: self.line,
+            column: self.column,
+            message,
+        }
+    }
+}
+
+#[derive(Debug)]
+enum Value {
+    Symbol(String),
+    // :name
+    Keyword(String),
+    Str(String),
+    Number(f64),
+    List(Vec<Expr>),
+}
+
+#[derive(Debug)]
+struct Expr {
+    value: Value,
+    pos: Pos,
+}
+
+impl Expr {
+    fn describe(&self) -> String {
+        match &self.value {
+            Value::Symbol(s) => format!("`{}`", s),
+            Value::Keyword(k) => format!("`:{}`", k),
+            Value::Str(s) => format!("string \"{}\"", s),
+            Value::Number(n) => format!("number {}", n),
+            Value::List(_) => String::from("a list"),
+        }
+    }
+
+    fn string(&self) -> Result<&str, MarkupError> {
+        match &self.value {
+            Value::Str(s) => Ok(s),
+            _ => Err(self.expected("a string")),
+        }
+    }
+
+    // 名字既可以是符号也可以是字符串
+    fn name(&self) -> Result<&str, MarkupError> {
+        match &self.value {
+            Value::Symbol(s) | Value::Str(s) => Ok(s),
+            _ => Err(self.expected("a name")),
+        }
+    }
+
+    fn number(&self) -> Result<f64, MarkupError> {
+        match self.value {
+            Value::Number(n) => Ok(n),
+            _ => Err(self.expected("a number")),
+        }
+    }
+
+    fn integer(&self) -> Result<u32, MarkupError> {
+        match self.value {
+            Value::Number(n) if n >= 0.0 && n.fract() == 0.0 && n <= u32::MAX as f64 => {
+                Ok(n as u32)
+            }
+            _ => Err(self.expected("a non-negative integer")),
+        }
+    }
+
+    fn boolean(&self) -> Result<bool, MarkupError> {
+        match &self.value {
+            Value::Symbol(s) if s == "true" => Ok(true),
+            Value::Symbol(s) if s == "false" => Ok(false),
+            _ => Err(self.expected("true or false")),
+        }
+    }
+
+    fn list(&self) -> Result<&[Expr], MarkupError> {
+        match &self.value {
+            Value::List(items) => Ok(items),
+            _ => Err(self.expected("a list")),
+        }
+    }
+
+    fn strings(&self) -> Result<Vec<&str>, MarkupError> {
+        self.list()?.iter().map(|e| e.string()).collect()
+    }
+
+    // (宽 高)
+    fn pair(&self) -> Result<(u32, u32), MarkupError> {
+        match self.list()? {
+            [a, b] => Ok((a.integer()?, b.integer()?)),
+            _ => Err(self.expected("a list of two integers")),
+        }
+    }
+
+    fn expected(&self, what: &str) -> MarkupError {
+        self.pos
+            .error(format!("expected {}, found {}", what, self.describe()))
+    }
+}
+
+struct Parser<'a> {
+    chars: std::iter::Peekable<std::str::Chars<'a>>,
+    line: usize,
+    column: usize,
+}
+
+impl Parser<'_> {
+    fn pos(&self) -> Pos {
+        Pos {
+            line: self.line,
+            column: self.column,
+        }
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.chars.next()?;
+        if c == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+        Some(c)
+    }
+
+    // 跳过空白和 ; 开始的注释
+    fn skip_space(&mut self) {
+        while let Some(&c) = self.chars.peek() {
+            if c == ';' {
+                while self.chars.peek().is_some_and(|&c| c != '\n') {
+                    self.bump();
+                }
+            } else if c.is_whitespace() {
+                self.bump();
+            } else {
+                break;
+            }
+        }
+    }
+
+    // 读到文件末尾或 ')' 为止
+    fn parse_list(&mut self, open: Option<Pos>) -> Result<Vec<Expr>, MarkupError> {
+        let mut items = Vec::new();
+        loop {
+            self.skip_space();
+            let pos = self.pos();
+            let value = match self.chars.peek() {
+                None => match open {
+                    Some(open) => return Err(open.error(String::from("unclosed `(`"))),
+                    None => return Ok(items),
+                },
+                Some(')') => {
+                    self.bump();
+                    match open {
+                        Some(_) => return Ok(items),
+                        None => return Err(pos.error(String::from("unexpected `)`"))),
+                    }
+                }
+                Some('(') => {
+                    self.bump();
+                    Value::List(self.parse_list(Some(pos))?)
+                }
+                Some('"') => {
+                    self.bump();
+                    Value::Str(self.parse_string(pos)?)
+                }
+                Some(_) => self.parse_atom(pos)?,
+            };
+            items.push(Expr { value, pos });
+        }
+    }
+
+    fn parse_string(&mut self, open: Pos) -> Result<String, MarkupError> {
+        let mut out = String::new();
+        loop {
+            let pos = self.pos();
+            match self.bump() {
+                None => return Err(open.error(String::from("unterminated string"))),
+                Some('"') => return Ok(out),
+                Some('\\') => match self.bump() {
+                    Some('n') => out.push('\n'),
+                    Some('t') => out.push('\t'),
+                    Some(c @ ('"' | '\\')) => out.push(c),
+                    Some(c) => return Err(pos.error(format!("unknown escape `\\{}`", c))),
+                    None => return Err(open.error(String::from("unterminated string"))),
+                },
+                Some(c) => out.push(c),
+            }
+        }
+    }
+
+    fn parse_atom(&mut self, pos: Pos) -> Result<Value, MarkupError> {
+        let mut text = String::new();
+        while let Some(&c) = self.chars.peek() {
+            if c.is_whitespace() || "();\"".contains(c) {
+                break;
+            }
+            text.push(c);
+            self.bump();
+        }
+        if let Some(name) = text.strip_prefix(':') {
+            if name.is_empty() {
+                return Err(pos.error(String::from("missing property name after `:`")));
+            }
+            return Ok(Value::Keyword(String::from(name)));
+        }
+        if text.starts_with(|c: char| c.is_ascii_digit() || c == '-' || c == '.') {
+            return match text.parse() {
+                Ok(n) => Ok(Value::Number(n)),
+                Err(_) => Err(pos.error(format!("invalid number `{}`", text))),
+            };
+        }
+        Ok(Value::Symbol(text))
+    }
+}
+
+// 一个 (类型 ...) 表达式拆分成位置参数、属性和子元素
+struct Form<'a> {
+    kind: &'a str,
+    pos: Pos,
+    args: Vec<&'a Expr>,
+    props: Vec<(&'a str, &'a Expr, Pos)>,
+}
+
+impl<'a> Form<'a> {
+    fn new(expr: &'a Expr) -> Result<Form<'a>, MarkupError> {
+        let items = match &expr.value {
+            Value::List(items) => items,
+            _ => return Err(expr.expected("a widget like `(label \"text\")`")),
+        };
+        let (head, rest) = match items.split_first() {
+            Some(split) => split,
+            None => return Err(expr.pos.error(String::from("empty widget `()`"))),
+        };
+        let kind = match &head.value {
+            Value::Symbol(s) => s.as_str(),
+            _ => return Err(head.expected("a widget name")),
+        };
+        let mut form = Form {
+            kind,
+            pos: expr.pos,
+            args: Vec::new(),
+            props: Vec::new(),
+        };
+        let mut rest = rest.iter();
+        while let Some(item) = rest.next() {
+            match &item.value {
+                Value::Keyword(name) => match rest.next() {
+                    Some(value) if !matches!(value.value, Value::Keyword(_)) => {
+                        if form.props.iter().any(|(n, _, _)| n == name) {
+                            return Err(item.pos.error(format!("duplicate property `:{}`", name)));
+                        }
+                        form.props.push((name, value, item.pos));
+                    }
+                    _ => {
+                        return Err(item.pos.error(format!("missing value for `:{}`", name)));
+                    }
+                },
+                _ => form.args.push(item),
+            }
+        }
+        Ok(form)
+    }
+
+    // 取出属性，取出后不再算作未知属性
+    fn take(&mut self, name: &str) -> Option<&'a Expr> {
+        let i = self.props.iter().position(|(n, _, _)| *n == name)?;
+        Some(self.props.remove(i).1)
+    }
+
+    // 组件最多有一个字符串参数
+    fn text(&mut self) -> Result<Option<&'a str>, MarkupError> {
+        match self.args.as_slice() {
+            [] => Ok(None),
+            [arg] => {
+                let text = arg.string()?;
+                self.args.clear();
+                Ok(Some(text))
+            }
+            [_, extra, ..] => Err(extra.pos.error(format!(
+                "unexpected {} in `{}`",
+                extra.describe(),
+                self.kind
+            ))),
+        }
+    }
+
+    fn require_text(&mut self) -> Result<&'a str, MarkupError> {
+        match self.text()? {
+            Some(text) => Ok(text),
+            None => Err(self
+                .pos
+                .error(format!("`{}` needs a text argument", self.kind))),
+        }
+    }
+
+    fn integer(&mut self, name: &str, default: u32) -> Result<u32, MarkupError> {
+        self.take(name).map_or(Ok(default), |e| e.integer())
+    }
+
+    fn boolean(&mut self, name: &str) -> Result<bool, MarkupError> {
+        self.take(name).map_or(Ok(false), |e| e.boolean())
+    }
+
+    // 所有属性都应该被取走
+    fn finish(&self) -> Result<(), MarkupError> {
+        if let Some(arg) = self.args.first() {
+            return Err(arg
+                .pos
+                .error(format!("unexpected {} in `{}`", arg.describe(), self.kind)));
+        }
+        match self.props.first() {
+            Some((name, _, pos)) => {
+                Err(pos.error(format!("unknown property `:{}` for `{}`", name, self.kind)))
+            }
+            None => Ok(()),
+        }
+    }
+}
+
+fn parse_align(expr: &Expr) -> Result<Align, MarkupError> {
+    match expr.name()? {
+        "start" => Ok(Align::Start),
+        "center" => Ok(Align::Center),
+        "end" => Ok(Align::End),
+        "stretch" => Ok(Align::Stretch),
+        other => Err(expr.pos.error(format!(
+            "unknown alignment `{}`, expected start, center, end or stretch",
+            other
+        ))),
+    }
+}
+
+// 容器和组件共用的布局属性
+fn apply_layout(form: &mut Form, mut layout: Layout) -> Result<Layout, MarkupError> {
+    if let Some(expr) = form.take("align") {
+        // 一个值同时用于两个方向，或者 (水平 垂直)
+        let (h, v) = match &expr.value {
+            Value::List(items) => match items.as_slice() {
+                [h, v] => (parse_align(h)?, parse_align(v)?),
+                _ => return Err(expr.expected("an alignment or a list of two")),
+            },
+            _ => {
+                let align = parse_align(expr)?;
+                (align, align)
+            }
+        };
+        layout = layout.align(h, v);
+    }
+    if let Some(expr) = form.take("padding") {
+        layout = layout.padding(Insets::all(expr.integer()?));
+    }
+    if let Some(expr) = form.take("min") {
+        let (w, h) = expr.pair()?;
+        layout = layout.min(w, h);
+    }
+    if let Some(expr) = form.take("max") {
+        let (w, h) = expr.pair()?;
+        layout = layout.max(w, h);
+    }
+    Ok(layout)
+}
+
+fn container_layout(form: &mut Form) -> Result<Option<Layout>, MarkupError> {
+    let layout = match form.kind {
+        "vertical" => Layout::vertical(vec![]),
+        "horizontal" => Layout::horizontal(vec![]),
+        "grid" => Layout::grid(form.integer("columns", 1)? as usize, vec![]),
+        _ => return Ok(None),
+    };
+    let spacing = form.integer("spacing", 0)?;
+    Ok(Some(apply_layout(form, layout.spacing(spacing))?))
+}
+
+fn build_widget(form: &mut Form) -> Result<Box<dyn Widget>, MarkupError> {
+    let widget: Box<dyn Widget> = match form.kind {
+        "label" => {
+            let mut label = Label::new(form.require_text()?);
+            if let Some(expr) = form.take("color") {
+                label.color = Some(parse_color(expr.name()?).map_err(|m| expr.pos.error(m))?);
+            }
+            Box::new(label)
+        }
+        "button" => {
+            let text = form.require_text()?;
+            let width = form.integer("width", text.chars().count() as u32 + 4)?;
+            let mut button = Button::new(width, form.integer("height", 3)?, text);
+            button.disabled = form.boolean("disabled")?;
+            Box::new(button)
+        }
+        "checkbox" => {
+            let mut checkbox = Checkbox::new(form.require_text()?);
+            checkbox.checked = form.boolean("checked")?;
+            checkbox.disabled = form.boolean("disabled")?;
+            Box::new(checkbox)
+        }
+        "input" => {
+            let mut input = TextInput::new(form.integer("width", 20)?);
+            if let Some(expr) = form.take("value") {
+                input.set_value(expr.string()?);
+            }
+            input.disabled = form.boolean("disabled")?;
+            Box::new(input)
+        }
+        "select" => {
+            let options = match form.take("options") {
+                Some(expr) => expr.strings()?,
+                None => Vec::new(),
+            };
+            let width = form.integer("width", 20)?;
+            let height = form.integer("height", options.len() as u32 + 2)?;
+            let mut select = SelectBox::new(width, height, &options);
+            select.disabled = form.boolean("disabled")?;
+            Box::new(select)
+        }
+        "list" => {
+            let items = match form.take("items") {
+                Some(expr) => expr.strings()?.into_iter().map(String::from).collect(),
+                None => Vec::new(),
+            };
+            let width = form.integer("width", 20)?;
+            let mut list = ListView::new(width, form.integer("height", 5)?, items);
+            list.disabled = form.boolean("disabled")?;
+            Box::new(list)
+        }
+        "progress" => {
+            let mut progress = ProgressBar::new(form.integer("width", 20)?);
+            if let Some(expr) = form.take("value") {
+                progress.set_value(expr.number()?);
+            }
+            Box::new(progress)
+        }
+        "table" => {
+            let headers = match form.take("headers") {
+                Some(expr) => expr.strings()?,
+                None => Vec::new(),
+            };
+            let width = form.integer("width", 40)?;
+            let mut table = Table::new(width, form.integer("height", 5)?, &headers);
+            if let Some(expr) = form.take("rows") {
+                for row in expr.list()? {
+                    table.add_row(&row.strings()?);
+                }
+            }
+            table.disabled = form.boolean("disabled")?;
+            Box::new(table)
+        }
+        other => return Err(form.pos.error(format!("unknown widget `{}`", other))),
+    };
+    Ok(widget)
+}
+
+// parent 总是根节点或刚创建的容器
+fn build(screen: &mut Screen, parent: WidgetId, expr: &Expr) -> Result<(), MarkupError> {
+    let mut form = Form::new(expr)?;
+    let id = match container_layout(&mut form)? {
+        Some(layout) => {
+            let id = screen
+                .add_container(parent, layout)
+                .expect("parent is a container");
+            // 容器的位置参数是子元素
+            for child in std::mem::take(&mut form.args) {
+                build(screen, id, child)?;
+            }
+            id
+        }
+        None => {
+            let widget = build_widget(&mut form)?;
+            let layout = apply_layout(&mut form, Layout::item(0))?;
+            let id = screen
+                .tree_mut()
+                .add_boxed(parent, widget)
+                .expect("parent is a container");
+            screen.tree_mut().set_layout(id, layout);
+            id
+        }
+    };
+    if let Some(expr) = form.take("id") {
+        screen.tree_mut().set_name(id, expr.name()?);
+    }
+    form.finish()
+}
+
+impl Screen {
+    pub fn parse(text: &str) -> Result<Screen, MarkupError> {
+        let mut parser = Parser {
+            chars: text.chars().peekable(),
+            line: 1,
+            column: 1,
+        };
+        let exprs = parser.parse_list(None)?;
+        let root = match exprs.as_slice() {
+            [root] => root,
+            [] => return Err(parser.pos().error(String::from("empty screen description"))),
+            [_, extra, ..] => {
+                return Err(extra
+                    .pos
+                    .error(String::from("expected a single top-level container")))
+            }
+        };
+        let mut form = Form::new(root)?;
+        let layout = match container_layout(&mut form)? {
+            Some(layout) => layout,
+            None => {
+                return Err(form.pos.error(format!(
+                    "top level must be vertical, horizontal or grid, found `{}`",
+                    form.kind
+                )))
+            }
+        };
+        let mut screen = Screen::with_layout(layout);
+        let id = screen.root();
+        for child in std::mem::take(&mut form.args) {
+            build(&mut screen, id, child)?;
+        }
+        if let Some(expr) = form.take("id") {
+            screen.tree_mut().set_name(id, expr.name()?);
+        }
+        form.finish()?;
+        Ok(screen)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Screen, MarkupError> {
+        Screen::parse(&fs::read_to_string(path)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Color, Rect};
+
+    #[test]
+    fn builds_tree_from_description() {
+        let text = r#"
+            ; 登录界面
+            (vertical :spacing 1 :padding 1
+              (label "Name:" :color #ff0000)
+              (input :id name :width 12 :value "bob")
+              (horizontal :spacing 2 :id buttons
+                (button "OK" :id ok)
+                (button "Cancel" :disabled true :align (end start)))
+              (checkbox "Remember \"me\"" :checked true))
+        "#;
+        let screen = Screen::parse(text).unwrap();
+        let tree = screen.tree();
+        let name = tree.find("name").unwrap();
+        assert_eq!("bob", tree.get::<TextInput>(name).unwrap().value());
+        let ok = tree.find("ok").unwrap();
+        assert_eq!(Some(tree.find("buttons").unwrap()), tree.parent(ok));
+        assert_eq!(5, tree.leaves().len());
+        let label = tree.get::<Label>(tree.leaves()[0]).unwrap();
+        assert_eq!(Some(Color::Rgb(255, 0, 0)), label.color);
+        let checkbox = tree.get::<Checkbox>(tree.leaves()[4]).unwrap();
+        assert_eq!("Remember \"me\"", checkbox.label);
+        assert!(checkbox.checked);
+        let rects = screen.arrange(Rect::new(0, 0, 80, 24));
+        assert_eq!((ok, Rect::new(1, 7, 6, 3)), rects[2]);
+    }
+
+    #[test]
+    fn reports_errors_with_positions() {
+        let message = |text| match Screen::parse(text) {
+            Ok(_) => String::from("ok"),
+            Err(e) => e.to_string(),
+        };
+        assert_eq!(
+            "line 2, column 3: unknown widget `buton`",
+            message("(vertical\n  (buton \"OK\"))")
+        );
+        assert_eq!(
+            "line 1, column 24: unknown property `:colour` for `label`",
+            message("(vertical (label \"a\"   :colour red))")
+        );
+        assert_eq!(
+            "line 1, column 25: expected a non-negative integer, found string \"ten\"",
+            message("(vertical (input :width \"ten\"))")
+        );
+        assert_eq!(
+            "line 1, column 1: unclosed `(`",
+            message("(vertical (label \"a\")")
+        );
+        assert_eq!(
+            "line 1, column 11: unterminated string",
+            message("(vertical \"abc")
+        );
+        assert_eq!(
+            "line 1, column 1: top level must be vertical, horizontal or grid, found `label`",
+            message("(label \"a\")")
+        );
+        assert_eq!(
+            "line 1, column 11: `button` needs a text argument",
+            message("(vertical (button :width 4))")
+        );
+        assert_eq!(
+            "line 1, column 30: unknown alignment `middle`, expected start, center, end or stretch",
+            message("(vertical (label \"a\" :align (middle end)))")
+        );
+    }
+}
@@ -0,0 +1,256 @@
+// 像素后端：把组件光栅化到 RGBA 帧缓冲区，可以保存为 PNG 或 PPM
+// Canvas 的一个单位对应 CELL_WIDTH x CELL_HEIGHT 像素，和终端的字符格对应
+use crate::canvas::{Canvas, Color, Rect};
+use crate::font;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+pub const CELL_WIDTH: u32 = 6;
+pub const CELL_HEIGHT: u32 = 10;
+
+const BACKGROUND: [u8; 4] = [255, 255, 255, 255];
+const FOREGROUND: [u8; 4] = [0, 0, 0, 255];
+
+// Default 在前景和背景中分别表示黑色和白色
+fn rgba(color: Color, default: [u8; 4]) -> [u8; 4] {
+    match color.rgb() {
+        Some((r, g, b)) => [r, g, b, 255],
+        None => default,
+    }
+}
+
+pub struct Framebuffer {
+    width: u32,
+    height: u32,
+    pixels: Vec<[u8; 4]>,
+}
+
+impl Framebuffer {
+    // columns、rows 为 Canvas 单位，实际像素尺寸要乘以格子大小
+    pub fn new(columns: u32, rows: u32) -> Framebuffer {
+        let width = columns * CELL_WIDTH;
+        let height = rows * CELL_HEIGHT;
+        Framebuffer {
+            width,
+            height,
+            pixels: vec![BACKGROUND; (width * height) as usize],
+        }
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn pixel(&self, x: u32, y: u32) -> Option<[u8; 4]> {
+        if x < self.width && y < self.height {
+            Some(self.pixels[(y * self.width + x) as usize])
+        } else {
+            None
+        }
+    }
+
+    fn put(&mut self, x: u32, y: u32, rgba: [u8; 4]) {
+        if x < self.width && y < self.height {
+            self.pixels[(y * self.width + x) as usize] = rgba;
+        }
+    }
+
+    // P6 格式，丢弃透明通道
+    pub fn write_ppm(&self, out: &mut impl Write) -> io::Result<()> {
+        write!(out, "P6\n{} {}\n255\n", self.width, self.height)?;
+        for p in &self.pixels {
+            out.write_all(&p[..3])?;
+        }
+        Ok(())
+    }
+
+    // 8 位 RGBA 的 PNG，图像数据用不压缩的 deflate 块保存
+    pub fn write_png(&self, out: &mut impl Write) -> io::Result<()> {
+        out.write_all(b"\x89PNG\r\n\x1a\n")?;
+
+        let mut header = Vec::new();
+        header.extend_from_slice(&self.width.to_be_bytes());
+        header.extend_from_slice(&self.height.to_be_bytes());
+        // 位深 8，颜色类型 6（RGBA），默认压缩、过滤，不隔行
+        header.extend_from_slice(&[8, 6, 0, 0, 0]);
+        write_chunk(out, b"IHDR", &header)?;
+
+        // 每行前面加一个过滤类型字节 0
+        let mut raw = Vec::with_capacity(((self.width * 4 + 1) * self.height) as usize);
+        for row in self.pixels.chunks(self.width.max(1) as usize) {
+            raw.push(0);
+            for p in row {
+                raw.extend_from_slice(p);
+            }
+        }
+        write_chunk(out, b"IDAT", &zlib_stored(&raw))?;
+        write_chunk(out, b"IEND", &[])
+    }
+
+    // 根据扩展名选择格式，.ppm 之外都保存为 PNG
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let path = path.as_ref();
+        let mut out = BufWriter::new(File::create(path)?);
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("ppm") => self.write_ppm(&mut out)?,
+            _ => self.write_png(&mut out)?,
+        }
+        out.flush()
+    }
+}
+
+impl Canvas for Framebuffer {
+    fn size(&self) -> (u32, u32) {
+        (self.width / CELL_WIDTH, self.height / CELL_HEIGHT)
+    }
+
+    fn fill_rect(&mut self, rect: Rect, color: Color) {
+        let rgba = rgba(color, BACKGROUND);
+        for y in rect.y * CELL_HEIGHT..rect.bottom() * CELL_HEIGHT {
+            for x in rect.x * CELL_WIDTH..rect.right() * CELL_WIDTH {
+                self.put(x, y, rgba);
+            }
+        }
+    }
+
+    // 边框画在四周格子的中线上，和终端的制表符位置一致
+    fn draw_box(&mut self, rect: Rect, color: Color) {
+        if rect.width == 0 || rect.height == 0 {
+            return;
+        }
+        let rgba = rgba(color, FOREGROUND);
+        let left = rect.x * CELL_WIDTH + CELL_WIDTH / 2;
+        let right = (rect.right() - 1) * CELL_WIDTH + CELL_WIDTH / 2;
+        let top = rect.y * CELL_HEIGHT + CELL_HEIGHT / 2;
+        let bottom = (rect.bottom() - 1) * CELL_HEIGHT + CELL_HEIGHT / 2;
+        for x in left..=right {
+            self.put(x, top, rgba);
+            self.put(x, bottom, rgba);
+        }
+        for y in top..=bottom {
+            self.put(left, y, rgba);
+            self.put(right, y, rgba);
+        }
+    }
+
+    // 字形放在格子中间，只画有点的像素
+    fn draw_text(&mut self, x: u32, y: u32, text: &str, color: Color) {
+        let rgba = rgba(color, FOREGROUND);
+        let dx = (CELL_WIDTH - font::GLYPH_WIDTH) / 2;
+        let dy = (CELL_HEIGHT - font::GLYPH_HEIGHT) / 2;
+        for (i, ch) in text.chars().enumerate() {
+            let left = (x + i as u32) * CELL_WIDTH + dx;
+            let top = y * CELL_HEIGHT + dy;
+            for row in 0..font::GLYPH_HEIGHT {
+                for column in 0..font::GLYPH_WIDTH {
+                    if font::pixel(ch, column, row) {
+                        self.put(left + column, top + row, rgba);
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn write_chunk(out: &mut impl Write, kind: &[u8; 4], data: &[u8]) -> io::Result<()> {
+    out.write_all(&(data.len() as u32).to_be_bytes())?;
+    out.write_all(kind)?;
+    out.write_all(data)?;
+    let crc = crc32(&[kind.as_slice(), data].concat());
+    out.write_all(&crc.to_be_bytes())
+}
+
+// zlib 格式，每个 deflate 块最多 65535 字节且不压缩
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    let mut out = vec![0x78, 0x01];
+    let mut blocks = data.chunks(0xFFFF).peekable();
+    if blocks.peek().is_none() {
+        out.extend_from_slice(&[1, 0, 0, 0xFF, 0xFF]);
+    }
+    while let Some(block) = blocks.next() {
+        let last = blocks.peek().is_none();
+        let len = block.len() as u16;
+        out.push(last as u8);
+        out.extend_from_slice(&len.to_le_bytes());
+        out.extend_from_slice(&(!len).to_le_bytes());
+        out.extend_from_slice(block);
+    }
+    out.extend_from_slice(&adler32(data).to_be_bytes());
+    out
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = 0xFFFF_FFFFu32;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for &byte in data {
+        a = (a + byte as u32) % 65521;
+        b = (b + a) % 65521;
+    }
+    (b << 16) | a
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Button, Screen};
+
+    #[test]
+    fn checksums() {
+        assert_eq!(0xCBF4_3926, crc32(b"123456789"));
+        assert_eq!(0x11E6_0398, adler32(b"Wikipedia"));
+    }
+
+    #[test]
+    fn rasterizes_button() {
+        let mut screen = Screen::new();
+        screen.add(screen.root(), Button::new(6, 3, "OK"));
+        let mut fb = Framebuffer::new(8, 4);
+        screen.run(&mut fb);
+        assert_eq!((48, 40), (fb.width(), fb.height()));
+        // 按钮内部是蓝色，边框是白色，按钮外保持背景色
+        assert_eq!(Some(rgba(Color::Blue, BACKGROUND)), fb.pixel(1, 1));
+        assert_eq!(Some(rgba(Color::White, FOREGROUND)), fb.pixel(10, 5));
+        assert_eq!(Some(BACKGROUND), fb.pixel(40, 35));
+        // 'O' 在第 2 格，左边一列的第 2 行有点
+        let text = rgba(Color::White, FOREGROUND);
+        assert_eq!(Some(text), fb.pixel(2 * CELL_WIDTH, CELL_HEIGHT + 1 + 1));
+        assert_eq!(
+            Some(rgba(Color::Blue, BACKGROUND)),
+            fb.pixel(2 * CELL_WIDTH, CELL_HEIGHT + 1)
+        );
+    }
+
+    #[test]
+    fn writes_image_files() {
+        let mut fb = Framebuffer::new(1, 1);
+        fb.fill_rect(Rect::new(0, 0, 1, 1), Color::Rgb(1, 2, 3));
+        let mut ppm = Vec::new();
+        fb.write_ppm(&mut ppm).unwrap();
+        assert!(ppm.starts_with(b"P6\n6 10\n255\n\x01\x02\x03"));
+        assert_eq!(12 + 6 * 10 * 3, ppm.len());
+
+        let mut png = Vec::new();
+        fb.write_png(&mut png).unwrap();
+        assert!(png.starts_with(b"\x89PNG\r\n\x1a\n\0\0\0\x0dIHDR\0\0\0\x06\0\0\0\x0a"));
+        assert!(png.ends_with(b"\0\0\0\0IEND\xae\x42\x60\x82"));
+    }
+}
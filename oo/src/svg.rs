@@ -0,0 +1,182 @@
+// SVG 后端：每个组件输出为一个 <g> 分组，文字保留为 <text>，方便在浏览器里查看和修改样式
+// 颜色写在属性上，样式表中的规则优先级更高，可以覆盖它们
+use crate::canvas::{Canvas, Color, Rect};
+use crate::{Draw, Style};
+
+pub const CELL_WIDTH: u32 = 8;
+pub const CELL_HEIGHT: u32 = 16;
+const FONT_SIZE: u32 = 14;
+
+fn hex(color: Color, default: &str) -> String {
+    match color.rgb() {
+        Some((r, g, b)) => format!("#{:02x}{:02x}{:02x}", r, g, b),
+        None => String::from(default),
+    }
+}
+
+pub fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+pub struct SvgCanvas {
+    columns: u32,
+    rows: u32,
+    body: String,
+    depth: usize,
+}
+
+impl SvgCanvas {
+    pub fn new(columns: u32, rows: u32) -> SvgCanvas {
+        SvgCanvas {
+            columns,
+            rows,
+            body: String::new(),
+            depth: 1,
+        }
+    }
+
+    fn line(&mut self, element: &str) {
+        self.body.push_str(&"  ".repeat(self.depth));
+        self.body.push_str(element);
+        self.body.push('\n');
+    }
+
+    pub fn begin_group(&mut self, id: &str, class: &str) {
+        self.line(&format!(
+            "<g id=\"{}\" class=\"{}\">",
+            escape(id),
+            escape(class)
+        ));
+        self.depth += 1;
+    }
+
+    pub fn end_group(&mut self) {
+        self.depth = self.depth.saturating_sub(1).max(1);
+        self.line("</g>");
+    }
+
+    // 输出完整的 SVG 文档，stylesheet 放在 <style> 中
+    pub fn finish(self, stylesheet: Option<&str>) -> String {
+        let (width, height) = (self.columns * CELL_WIDTH, self.rows * CELL_HEIGHT);
+        let mut out = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" viewBox=\"0 0 {} {}\">\n",
+            width, height, width, height
+        );
+        if let Some(css) = stylesheet {
+            out.push_str(&format!("  <style>{}</style>\n", escape(css)));
+        }
+        out.push_str(&self.body);
+        out.push_str("</svg>\n");
+        out
+    }
+}
+
+impl Canvas for SvgCanvas {
+    fn size(&self) -> (u32, u32) {
+        (self.columns, self.rows)
+    }
+
+    fn fill_rect(&mut self, rect: Rect, color: Color) {
+        self.line(&format!(
+            "<rect class=\"fill\" x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"{}\"/>",
+            rect.x * CELL_WIDTH,
+            rect.y * CELL_HEIGHT,
+            rect.width * CELL_WIDTH,
+            rect.height * CELL_HEIGHT,
+            hex(color, "#ffffff")
+        ));
+    }
+
+    // 和其他后端一样，边框位于四周格子的中线上
+    fn draw_box(&mut self, rect: Rect, color: Color) {
+        if rect.width == 0 || rect.height == 0 {
+            return;
+        }
+        self.line(&format!(
+            "<rect class=\"border\" x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"none\" stroke=\"{}\"/>",
+            rect.x * CELL_WIDTH + CELL_WIDTH / 2,
+            rect.y * CELL_HEIGHT + CELL_HEIGHT / 2,
+            (rect.width - 1) * CELL_WIDTH,
+            (rect.height - 1) * CELL_HEIGHT,
+            hex(color, "#000000")
+        ));
+    }
+
+    fn draw_text(&mut self, x: u32, y: u32, text: &str, color: Color) {
+        // 基线放在格子偏下的位置
+        self.line(&format!(
+            "<text class=\"label\" x=\"{}\" y=\"{}\" font-family=\"monospace\" font-size=\"{}\" fill=\"{}\" xml:space=\"preserve\">{}</text>",
+            x * CELL_WIDTH,
+            y * CELL_HEIGHT + CELL_HEIGHT * 3 / 4,
+            FONT_SIZE,
+            hex(color, "#000000"),
+            escape(text)
+        ));
+    }
+}
+
+// 把布局好的组件逐个画进各自的分组
+pub fn render<'a>(
+    components: impl Iterator<Item = (&'a dyn Draw, Option<Rect>, Style)>,
+    columns: u32,
+    rows: u32,
+    stylesheet: Option<&str>,
+) -> String {
+    let mut canvas = SvgCanvas::new(columns, rows);
+    for (i, (component, rect, style)) in components.enumerate() {
+        if let Some(rect) = rect {
+            canvas.begin_group(&format!("component-{}", i), component.kind());
+            component.draw(&mut canvas, rect, &style);
+            canvas.end_group();
+        }
+    }
+    canvas.finish(stylesheet)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Button, Screen, Screen1, Theme};
+
+    #[test]
+    fn exports_screen_with_groups() {
+        let mut screen = Screen::new();
+        let root = screen.root();
+        screen.add(root, Button::new(6, 3, "<OK>"));
+        screen.add(root, Button::new(6, 3, "Cancel"));
+        let svg = screen.to_svg(10, 6, Some(".button .fill { fill: red; }"));
+        assert!(svg.starts_with(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"80\" height=\"96\" viewBox=\"0 0 80 96\">\n  <style>.button .fill { fill: red; }</style>\n"
+        ));
+        assert!(svg.contains("  <g id=\"component-0\" class=\"button\">\n    <rect class=\"fill\" x=\"0\" y=\"0\" width=\"48\" height=\"48\" fill=\"#2472c8\"/>\n"));
+        assert!(svg.contains("<g id=\"component-1\" class=\"button\">"));
+        assert!(svg.contains(">&lt;OK&gt;</text>"));
+        assert!(svg.contains(
+            "<rect class=\"border\" x=\"4\" y=\"56\" width=\"40\" height=\"32\" fill=\"none\" stroke=\"#e5e5e5\"/>"
+        ));
+        assert!(svg.ends_with("  </g>\n</svg>\n"));
+    }
+
+    #[test]
+    fn exports_generic_screen() {
+        let screen = Screen1 {
+            components: vec![Button::new(4, 3, "A")],
+            layout: None,
+            theme: Theme::dark(),
+        };
+        let svg = screen.to_svg(4, 3, None);
+        assert!(!svg.contains("<style>"));
+        assert_eq!(1, svg.matches("<g ").count());
+        assert!(svg.contains("<text class=\"label\" x=\"8\" y=\"28\""));
+    }
+}
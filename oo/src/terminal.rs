@@ -0,0 +1,213 @@
+// 终端后端：组件画到后台缓冲区，flush 时只输出和前台缓冲区不同的单元格
+use crate::canvas::{Canvas, Color, Rect};
+use std::io::{self, Write};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cell {
+    pub ch: char,
+    pub fg: Color,
+    pub bg: Color,
+}
+
+impl Default for Cell {
+    fn default() -> Self {
+        Cell {
+            ch: ' ',
+            fg: Color::Default,
+            bg: Color::Default,
+        }
+    }
+}
+
+// 不可能出现在后台缓冲区中的单元格，用来强制整屏重绘
+const INVALID: Cell = Cell {
+    ch: '\0',
+    fg: Color::Default,
+    bg: Color::Default,
+};
+
+fn ansi(color: Color, background: bool) -> String {
+    let base = if background { 40 } else { 30 };
+    match color {
+        Color::Default => format!("{}", base + 9),
+        Color::Black => format!("{}", base),
+        Color::Red => format!("{}", base + 1),
+        Color::Green => format!("{}", base + 2),
+        Color::Yellow => format!("{}", base + 3),
+        Color::Blue => format!("{}", base + 4),
+        Color::Magenta => format!("{}", base + 5),
+        Color::Cyan => format!("{}", base + 6),
+        Color::White => format!("{}", base + 7),
+        Color::Rgb(r, g, b) => format!("{};2;{};{};{}", base + 8, r, g, b),
+    }
+}
+
+pub struct Terminal {
+    width: u32,
+    height: u32,
+    front: Vec<Cell>,
+    back: Vec<Cell>,
+}
+
+impl Terminal {
+    pub fn new(width: u32, height: u32) -> Terminal {
+        let len = (width * height) as usize;
+        Terminal {
+            width,
+            height,
+            front: vec![INVALID; len],
+            back: vec![Cell::default(); len],
+        }
+    }
+
+    pub fn cell(&self, x: u32, y: u32) -> Option<Cell> {
+        if x < self.width && y < self.height {
+            Some(self.back[(y * self.width + x) as usize])
+        } else {
+            None
+        }
+    }
+
+    // 超出范围的写入直接丢弃
+    fn set(&mut self, x: u32, y: u32, f: impl FnOnce(&mut Cell)) {
+        if x < self.width && y < self.height {
+            f(&mut self.back[(y * self.width + x) as usize]);
+        }
+    }
+
+    // 清空后台缓冲区，开始新的一帧
+    pub fn clear(&mut self) {
+        self.back.fill(Cell::default());
+    }
+
+    // 下次 flush 时输出全部单元格，比如终端内容被其他程序破坏之后
+    pub fn invalidate(&mut self) {
+        self.front.fill(INVALID);
+    }
+
+    pub fn resize(&mut self, width: u32, height: u32) {
+        *self = Terminal::new(width, height);
+    }
+
+    // 输出变化的单元格；光标和颜色没变时不重复输出控制序列
+    pub fn flush(&mut self, out: &mut impl Write) -> io::Result<()> {
+        let mut cursor = None;
+        let mut style = None;
+        for (i, cell) in self.back.iter().enumerate() {
+            if *cell == self.front[i] {
+                continue;
+            }
+            let x = i as u32 % self.width;
+            let y = i as u32 / self.width;
+            if cursor != Some((x, y)) {
+                write!(out, "\x1b[{};{}H", y + 1, x + 1)?;
+            }
+            if style != Some((cell.fg, cell.bg)) {
+                write!(
+                    out,
+                    "\x1b[0;{};{}m",
+                    ansi(cell.fg, false),
+                    ansi(cell.bg, true)
+                )?;
+                style = Some((cell.fg, cell.bg));
+            }
+            write!(out, "{}", cell.ch)?;
+            cursor = Some((x + 1, y));
+        }
+        if style.is_some() {
+            write!(out, "\x1b[0m")?;
+        }
+        self.front.copy_from_slice(&self.back);
+        out.flush()
+    }
+}
+
+impl Canvas for Terminal {
+    fn size(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    fn fill_rect(&mut self, rect: Rect, color: Color) {
+        for y in rect.y..rect.bottom() {
+            for x in rect.x..rect.right() {
+                self.set(x, y, |c| {
+                    c.ch = ' ';
+                    c.bg = color;
+                });
+            }
+        }
+    }
+
+    fn draw_box(&mut self, rect: Rect, color: Color) {
+        if rect.width == 0 || rect.height == 0 {
+            return;
+        }
+        let (right, bottom) = (rect.right() - 1, rect.bottom() - 1);
+        for y in rect.y..=bottom {
+            for x in rect.x..=right {
+                let ch = match (x == rect.x, x == right, y == rect.y, y == bottom) {
+                    (true, _, true, _) => '┌',
+                    (_, true, true, _) => '┐',
+                    (true, _, _, true) => '└',
+                    (_, true, _, true) => '┘',
+                    (true, _, _, _) | (_, true, _, _) => '│',
+                    (_, _, true, _) | (_, _, _, true) => '─',
+                    _ => continue,
+                };
+                self.set(x, y, |c| {
+                    c.ch = ch;
+                    c.fg = color;
+                });
+            }
+        }
+    }
+
+    fn draw_text(&mut self, x: u32, y: u32, text: &str, color: Color) {
+        for (i, ch) in text.chars().enumerate() {
+            self.set(x + i as u32, y, |c| {
+                c.ch = ch;
+                c.fg = color;
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn flush(term: &mut Terminal) -> String {
+        let mut out = Vec::new();
+        term.flush(&mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn draws_boxes_and_text() {
+        let mut term = Terminal::new(6, 3);
+        term.draw_box(Rect::new(0, 0, 6, 3), Color::White);
+        term.draw_text(1, 1, "OK", Color::Red);
+        let rows: Vec<String> = (0..3)
+            .map(|y| (0..6).map(|x| term.cell(x, y).unwrap().ch).collect())
+            .collect();
+        assert_eq!(vec!["┌────┐", "│OK  │", "└────┘"], rows);
+        assert_eq!(Color::Red, term.cell(1, 1).unwrap().fg);
+        // 越界写入被裁掉
+        term.draw_text(5, 2, "long", Color::Red);
+        assert_eq!('l', term.cell(5, 2).unwrap().ch);
+    }
+
+    #[test]
+    fn flushes_only_changed_cells() {
+        let mut term = Terminal::new(4, 2);
+        let first = flush(&mut term);
+        assert!(first.starts_with("\x1b[1;1H\x1b[0;39;49m    "));
+        assert_eq!("", flush(&mut term));
+
+        term.draw_text(1, 1, "hi", Color::Green);
+        assert_eq!("\x1b[2;2H\x1b[0;32;49mhi\x1b[0m", flush(&mut term));
+
+        term.fill_rect(Rect::new(3, 0, 1, 1), Color::Rgb(1, 2, 3));
+        assert_eq!("\x1b[1;4H\x1b[0;39;48;2;1;2;3m \x1b[0m", flush(&mut term));
+    }
+}
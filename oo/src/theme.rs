@@ -0,0 +1,330 @@
+// 主题和样式：按组件类型和状态层叠，未设置的属性从上一层继承
+// 顺序为 "*" -> "*:状态" -> "类型" -> "类型:状态"，后面的覆盖前面的
+use crate::canvas::Color;
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Style {
+    pub fg: Option<Color>,
+    pub bg: Option<Color>,
+    pub border: Option<Color>,
+    // 选中项、光标、进度等强调色
+    pub accent: Option<Color>,
+    // 边框和内容之间的空白
+    pub padding: Option<u32>,
+}
+
+impl Style {
+    pub fn fg(&self) -> Color {
+        self.fg.unwrap_or_default()
+    }
+
+    pub fn bg(&self) -> Color {
+        self.bg.unwrap_or_default()
+    }
+
+    pub fn border(&self) -> Color {
+        self.border.unwrap_or_default()
+    }
+
+    pub fn accent(&self) -> Color {
+        self.accent.unwrap_or(Color::Blue)
+    }
+
+    pub fn padding(&self) -> u32 {
+        self.padding.unwrap_or(0)
+    }
+
+    // 自己没有设置的属性使用 base 的值
+    pub fn over(self, base: &Style) -> Style {
+        Style {
+            fg: self.fg.or(base.fg),
+            bg: self.bg.or(base.bg),
+            border: self.border.or(base.border),
+            accent: self.accent.or(base.accent),
+            padding: self.padding.or(base.padding),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum State {
+    Normal,
+    Focused,
+    Disabled,
+}
+
+impl State {
+    fn suffix(self) -> &'static str {
+        match self {
+            State::Normal => "",
+            State::Focused => ":focused",
+            State::Disabled => ":disabled",
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ThemeError {
+    Io(io::Error),
+    // 行号从 1 开始
+    Syntax { line: usize, message: String },
+}
+
+impl fmt::Display for ThemeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ThemeError::Io(e) => write!(f, "io error: {}", e),
+            ThemeError::Syntax { line, message } => write!(f, "line {}: {}", line, message),
+        }
+    }
+}
+
+impl std::error::Error for ThemeError {}
+
+impl From<io::Error> for ThemeError {
+    fn from(e: io::Error) -> Self {
+        ThemeError::Io(e)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Theme {
+    // 选择器 -> 样式，选择器形如 "*"、"button"、"button:focused"
+    rules: HashMap<String, Style>,
+}
+
+impl Theme {
+    // 深色终端的默认外观
+    pub fn dark() -> Theme {
+        let mut theme = Theme::default();
+        theme.set("*", |s| s.border = Some(Color::White));
+        theme.set("*:focused", |s| s.border = Some(Color::Yellow));
+        theme.set("*:disabled", |s| {
+            s.fg = Some(Color::Rgb(128, 128, 128));
+            s.border = Some(Color::Rgb(128, 128, 128));
+        });
+        theme.set("button", |s| {
+            s.fg = Some(Color::White);
+            s.bg = Some(Color::Blue);
+        });
+        theme.set("checkbox:focused", |s| s.fg = Some(Color::Yellow));
+        theme.set("select", |s| s.padding = Some(1));
+        theme.set("select:focused", |s| s.accent = Some(Color::Yellow));
+        theme.set("progress", |s| {
+            s.fg = Some(Color::White);
+            s.bg = Some(Color::Black);
+            s.accent = Some(Color::Green);
+        });
+        theme
+    }
+
+    pub fn light() -> Theme {
+        let mut theme = Theme::default();
+        theme.set("*", |s| {
+            s.fg = Some(Color::Black);
+            s.bg = Some(Color::Rgb(250, 250, 250));
+            s.border = Some(Color::Rgb(96, 96, 96));
+            s.accent = Some(Color::Cyan);
+        });
+        theme.set("*:focused", |s| s.border = Some(Color::Blue));
+        theme.set("*:disabled", |s| {
+            s.fg = Some(Color::Rgb(160, 160, 160));
+            s.border = Some(Color::Rgb(200, 200, 200));
+        });
+        theme.set("button", |s| {
+            s.fg = Some(Color::Black);
+            s.bg = Some(Color::Rgb(220, 220, 220));
+        });
+        theme.set("checkbox:focused", |s| s.fg = Some(Color::Blue));
+        theme.set("select", |s| s.padding = Some(1));
+        theme.set("select:focused", |s| s.accent = Some(Color::Blue));
+        theme.set("progress", |s| {
+            s.bg = Some(Color::Rgb(220, 220, 220));
+            s.accent = Some(Color::Green);
+        });
+        theme
+    }
+
+    // 修改某个选择器的样式，没有时新建
+    pub fn set(&mut self, selector: &str, f: impl FnOnce(&mut Style)) {
+        f(self.rules.entry(String::from(selector)).or_default());
+    }
+
+    pub fn get(&self, selector: &str) -> Option<&Style> {
+        self.rules.get(selector)
+    }
+
+    // 根据组件类型和状态计算最终样式
+    pub fn resolve(&self, kind: &str, state: State) -> Style {
+        let selectors = [
+            String::from("*"),
+            format!("*{}", state.suffix()),
+            String::from(kind),
+            format!("{}{}", kind, state.suffix()),
+        ];
+        selectors
+            .iter()
+            .filter_map(|s| self.rules.get(s))
+            .fold(Style::default(), |base, style| style.over(&base))
+    }
+
+    // 在另一个主题的基础上覆盖，用于只修改部分属性的配置文件
+    pub fn over(mut self, base: &Theme) -> Theme {
+        for (selector, style) in &base.rules {
+            let merged = match self.rules.get(selector) {
+                Some(own) => own.over(style),
+                None => *style,
+            };
+            self.rules.insert(selector.clone(), merged);
+        }
+        self
+    }
+
+    // 配置文件格式：
+    //   # 注释
+    //   [button:focused]
+    //   fg = white
+    //   bg = #2472c8
+    //   padding = 1
+    pub fn parse(text: &str) -> Result<Theme, ThemeError> {
+        let mut theme = Theme::default();
+        let mut selector: Option<String> = None;
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim();
+            let error = |message: String| ThemeError::Syntax {
+                line: i + 1,
+                message,
+            };
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                let name = name.trim();
+                check_selector(name).map_err(error)?;
+                theme.rules.entry(String::from(name)).or_default();
+                selector = Some(String::from(name));
+                continue;
+            }
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| error(format!("expected `key = value`, found `{}`", line)))?;
+            let selector = selector
+                .as_ref()
+                .ok_or_else(|| error(String::from("property outside of a [section]")))?;
+            let style = theme.rules.get_mut(selector).unwrap();
+            let value = value.trim();
+            match key.trim() {
+                "fg" => style.fg = Some(parse_color(value).map_err(error)?),
+                "bg" => style.bg = Some(parse_color(value).map_err(error)?),
+                "border" => style.border = Some(parse_color(value).map_err(error)?),
+                "accent" => style.accent = Some(parse_color(value).map_err(error)?),
+                "padding" => {
+                    let padding = value
+                        .parse()
+                        .map_err(|_| error(format!("invalid padding `{}`", value)))?;
+                    style.padding = Some(padding);
+                }
+                other => return Err(error(format!("unknown property `{}`", other))),
+            }
+        }
+        Ok(theme)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Theme, ThemeError> {
+        Theme::parse(&fs::read_to_string(path)?)
+    }
+}
+
+fn check_selector(selector: &str) -> Result<(), String> {
+    let (kind, state) = match selector.split_once(':') {
+        Some((kind, state)) => (kind, Some(state)),
+        None => (selector, None),
+    };
+    let valid_kind =
+        kind == "*" || (!kind.is_empty() && kind.chars().all(|c| c.is_ascii_alphanumeric()));
+    if !valid_kind {
+        return Err(format!("invalid selector `{}`", selector));
+    }
+    match state {
+        None | Some("focused") | Some("disabled") => Ok(()),
+        Some(other) => Err(format!("unknown state `{}`", other)),
+    }
+}
+
+// 颜色名或 #rrggbb
+pub fn parse_color(value: &str) -> Result<Color, String> {
+    let color = match value.to_ascii_lowercase().as_str() {
+        "default" => Color::Default,
+        "black" => Color::Black,
+        "red" => Color::Red,
+        "green" => Color::Green,
+        "yellow" => Color::Yellow,
+        "blue" => Color::Blue,
+        "magenta" => Color::Magenta,
+        "cyan" => Color::Cyan,
+        "white" => Color::White,
+        hex => {
+            let digits = hex
+                .strip_prefix('#')
+                .filter(|d| d.len() == 6 && d.chars().all(|c| c.is_ascii_hexdigit()))
+                .ok_or_else(|| format!("invalid color `{}`", value))?;
+            let channel = |i: usize| u8::from_str_radix(&digits[i..i + 2], 16).unwrap();
+            Color::Rgb(channel(0), channel(2), channel(4))
+        }
+    };
+    Ok(color)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cascades_from_generic_to_specific() {
+        let theme = Theme::dark();
+        let button = theme.resolve("button", State::Focused);
+        assert_eq!(Color::White, button.fg());
+        assert_eq!(Color::Blue, button.bg());
+        assert_eq!(Color::Yellow, button.border());
+        let disabled = theme.resolve("button", State::Disabled);
+        assert_eq!(Color::Rgb(128, 128, 128), disabled.border());
+        // 按钮自己的前景色比 "*:disabled" 更具体
+        assert_eq!(Color::White, disabled.fg());
+        assert_eq!(1, theme.resolve("select", State::Normal).padding());
+        assert_eq!(0, theme.resolve("list", State::Normal).padding());
+    }
+
+    #[test]
+    fn parses_config_files() {
+        let text = "# 只改按钮\n[button]\nbg = #102030\n\n[button:focused]\nborder = red\n";
+        let theme = Theme::parse(text).unwrap().over(&Theme::dark());
+        let button = theme.resolve("button", State::Focused);
+        assert_eq!(Color::Rgb(0x10, 0x20, 0x30), button.bg());
+        assert_eq!(Color::White, button.fg());
+        assert_eq!(Color::Red, button.border());
+    }
+
+    #[test]
+    fn reports_errors_with_line_numbers() {
+        let message = |text| Theme::parse(text).unwrap_err().to_string();
+        assert_eq!(
+            "line 1: property outside of a [section]",
+            message("fg = red")
+        );
+        assert_eq!("line 2: invalid color `#12`", message("[*]\nfg = #12"));
+        assert_eq!("line 1: unknown state `hover`", message("[button:hover]"));
+        assert_eq!(
+            "line 3: unknown property `colour`",
+            message("[*]\n\ncolour = red")
+        );
+        assert_eq!(
+            "line 2: expected `key = value`, found `fg red`",
+            message("[*]\nfg red")
+        );
+    }
+}
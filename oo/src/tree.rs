@@ -0,0 +1,356 @@
+// 保留模式的组件树：容器和叶子组件都有稳定的 id，修改过的节点标记为脏，
+// 重绘时只需要处理脏节点所在的子树
+use crate::canvas::Rect;
+use crate::layout::Layout;
+use crate::Widget;
+use std::any::Any;
+use std::collections::{BTreeMap, HashMap};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct WidgetId(u32);
+
+enum Content {
+    Leaf(Box<dyn Widget>),
+    Container(Vec<WidgetId>),
+}
+
+struct Node {
+    parent: Option<WidgetId>,
+    // 容器使用完整的布局；叶子只使用对齐、内边距和尺寸约束
+    layout: Layout,
+    content: Content,
+    dirty: bool,
+}
+
+pub struct WidgetTree {
+    nodes: BTreeMap<WidgetId, Node>,
+    root: WidgetId,
+    next_id: u32,
+    // 名字 -> id，方便从界面描述文件中引用组件
+    names: HashMap<String, WidgetId>,
+    // 增删节点或修改布局后需要重新排版
+    structure_changed: bool,
+}
+
+impl WidgetTree {
+    // 根节点是按 layout 排列的容器
+    pub fn new(layout: Layout) -> WidgetTree {
+        let root = WidgetId(0);
+        let mut nodes = BTreeMap::new();
+        nodes.insert(
+            root,
+            Node {
+                parent: None,
+                layout,
+                content: Content::Container(Vec::new()),
+                dirty: true,
+            },
+        );
+        WidgetTree {
+            nodes,
+            root,
+            next_id: 1,
+            names: HashMap::new(),
+            structure_changed: true,
+        }
+    }
+
+    pub fn root(&self) -> WidgetId {
+        self.root
+    }
+
+    pub fn contains(&self, id: WidgetId) -> bool {
+        self.nodes.contains_key(&id)
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    // 只有根节点时为空
+    pub fn is_empty(&self) -> bool {
+        self.nodes.len() == 1
+    }
+
+    fn insert(&mut self, parent: WidgetId, layout: Layout, content: Content) -> Option<WidgetId> {
+        let id = WidgetId(self.next_id);
+        match self.nodes.get_mut(&parent).map(|n| &mut n.content) {
+            Some(Content::Container(children)) => children.push(id),
+            _ => return None,
+        }
+        self.next_id += 1;
+        self.nodes.insert(
+            id,
+            Node {
+                parent: Some(parent),
+                layout,
+                content,
+                dirty: true,
+            },
+        );
+        self.structure_changed = true;
+        Some(id)
+    }
+
+    // 在容器末尾添加组件；parent 不是容器或已被删除时返回 None
+    pub fn add(&mut self, parent: WidgetId, widget: impl Widget) -> Option<WidgetId> {
+        self.add_boxed(parent, Box::new(widget))
+    }
+
+    pub fn add_boxed(&mut self, parent: WidgetId, widget: Box<dyn Widget>) -> Option<WidgetId> {
+        self.insert(parent, Layout::item(0), Content::Leaf(widget))
+    }
+
+    // 添加子容器，layout 决定子元素的排列方式，其中的子元素会被忽略
+    pub fn add_container(&mut self, parent: WidgetId, layout: Layout) -> Option<WidgetId> {
+        self.insert(parent, layout, Content::Container(Vec::new()))
+    }
+
+    // 删除节点和它的整个子树，根节点不能删除
+    pub fn remove(&mut self, id: WidgetId) -> bool {
+        let parent = match self.nodes.get(&id).and_then(|n| n.parent) {
+            Some(parent) => parent,
+            None => return false,
+        };
+        if let Some(Content::Container(children)) =
+            self.nodes.get_mut(&parent).map(|n| &mut n.content)
+        {
+            children.retain(|c| *c != id);
+        }
+        let mut stack = vec![id];
+        while let Some(id) = stack.pop() {
+            if let Some(Node {
+                content: Content::Container(children),
+                ..
+            }) = self.nodes.remove(&id)
+            {
+                stack.extend(children);
+            }
+        }
+        self.names.retain(|_, id| self.nodes.contains_key(id));
+        self.structure_changed = true;
+        true
+    }
+
+    // 同名时后设置的覆盖前面的
+    pub fn set_name(&mut self, id: WidgetId, name: &str) {
+        if self.contains(id) {
+            self.names.insert(String::from(name), id);
+        }
+    }
+
+    pub fn find(&self, name: &str) -> Option<WidgetId> {
+        self.names.get(name).copied()
+    }
+
+    pub fn parent(&self, id: WidgetId) -> Option<WidgetId> {
+        self.nodes.get(&id).and_then(|n| n.parent)
+    }
+
+    pub fn children(&self, id: WidgetId) -> &[WidgetId] {
+        match self.nodes.get(&id).map(|n| &n.content) {
+            Some(Content::Container(children)) => children,
+            _ => &[],
+        }
+    }
+
+    pub fn layout(&self, id: WidgetId) -> Option<&Layout> {
+        self.nodes.get(&id).map(|n| &n.layout)
+    }
+
+    pub fn set_layout(&mut self, id: WidgetId, layout: Layout) {
+        if let Some(node) = self.nodes.get_mut(&id) {
+            node.layout = layout;
+            self.structure_changed = true;
+        }
+    }
+
+    // 容器没有对应的组件
+    pub fn widget(&self, id: WidgetId) -> Option<&dyn Widget> {
+        match self.nodes.get(&id).map(|n| &n.content) {
+            Some(Content::Leaf(widget)) => Some(widget.as_ref()),
+            _ => None,
+        }
+    }
+
+    // 取得可修改的组件，同时把它标记为脏
+    pub fn widget_mut(&mut self, id: WidgetId) -> Option<&mut dyn Widget> {
+        let node = self.nodes.get_mut(&id)?;
+        match &mut node.content {
+            Content::Leaf(widget) => {
+                node.dirty = true;
+                Some(widget.as_mut())
+            }
+            Content::Container(_) => None,
+        }
+    }
+
+    // 不标记为脏，由调用者决定是否需要重绘
+    pub(crate) fn widget_mut_clean(&mut self, id: WidgetId) -> Option<&mut dyn Widget> {
+        match self.nodes.get_mut(&id).map(|n| &mut n.content) {
+            Some(Content::Leaf(widget)) => Some(widget.as_mut()),
+            _ => None,
+        }
+    }
+
+    // 按具体类型查找组件
+    pub fn get<T: Widget>(&self, id: WidgetId) -> Option<&T> {
+        let any: &dyn Any = self.widget(id)?;
+        any.downcast_ref()
+    }
+
+    // 类型不对时返回 None，也不标记为脏
+    pub fn get_mut<T: Widget>(&mut self, id: WidgetId) -> Option<&mut T> {
+        self.get::<T>(id)?;
+        self.mark_dirty(id);
+        let any: &mut dyn Any = self.widget_mut_clean(id)?;
+        any.downcast_mut()
+    }
+
+    pub fn mark_dirty(&mut self, id: WidgetId) {
+        if let Some(node) = self.nodes.get_mut(&id) {
+            node.dirty = true;
+        }
+    }
+
+    // 节点自己或任一祖先被标记为脏
+    pub fn is_dirty(&self, id: WidgetId) -> bool {
+        let mut current = Some(id);
+        while let Some(id) = current {
+            match self.nodes.get(&id) {
+                Some(node) if node.dirty => return true,
+                Some(node) => current = node.parent,
+                None => return false,
+            }
+        }
+        false
+    }
+
+    pub(crate) fn structure_changed(&self) -> bool {
+        self.structure_changed
+    }
+
+    pub(crate) fn clear_dirty(&mut self) {
+        for node in self.nodes.values_mut() {
+            node.dirty = false;
+        }
+        self.structure_changed = false;
+    }
+
+    // 深度优先顺序的叶子，也是绘制和 Tab 切换焦点的顺序
+    pub fn leaves(&self) -> Vec<WidgetId> {
+        let mut out = Vec::new();
+        let mut stack = vec![self.root];
+        while let Some(id) = stack.pop() {
+            match self.nodes.get(&id).map(|n| &n.content) {
+                Some(Content::Leaf(_)) => out.push(id),
+                Some(Content::Container(children)) => stack.extend(children.iter().rev()),
+                None => {}
+            }
+        }
+        out
+    }
+
+    // 由树生成布局并计算每个叶子的矩形，结果按绘制顺序排列
+    pub fn arrange(&self, area: Rect) -> Vec<(WidgetId, Rect)> {
+        let leaves = self.leaves();
+        let sizes: Vec<(u32, u32)> = leaves
+            .iter()
+            .map(|id| self.widget(*id).map_or((0, 0), |w| w.size()))
+            .collect();
+        let mut slot = 0;
+        let layout = self.build_layout(self.root, &mut slot);
+        let rects = layout.arrange(area, &sizes);
+        leaves
+            .into_iter()
+            .zip(rects)
+            .filter_map(|(id, rect)| rect.map(|r| (id, r)))
+            .collect()
+    }
+
+    // slot 按深度优先顺序给叶子编号，和 leaves() 一致
+    fn build_layout(&self, id: WidgetId, slot: &mut usize) -> Layout {
+        let node = &self.nodes[&id];
+        match &node.content {
+            Content::Leaf(_) => {
+                *slot += 1;
+                node.layout.to_item(*slot - 1)
+            }
+            Content::Container(children) => {
+                let children = children
+                    .iter()
+                    .map(|c| self.build_layout(*c, slot))
+                    .collect();
+                node.layout.to_container(children)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Button, Checkbox, Label};
+
+    #[test]
+    fn builds_and_edits_tree() {
+        let mut tree = WidgetTree::new(Layout::vertical(vec![]));
+        let root = tree.root();
+        let title = tree.add(root, Label::new("title")).unwrap();
+        let row = tree
+            .add_container(root, Layout::horizontal(vec![]).spacing(1))
+            .unwrap();
+        let ok = tree.add(row, Button::new(4, 1, "OK")).unwrap();
+        let cancel = tree.add(row, Button::new(6, 1, "Cancel")).unwrap();
+        assert_eq!(vec![title, ok, cancel], tree.leaves());
+        assert_eq!(Some(row), tree.parent(ok));
+        assert_eq!(
+            vec![
+                (title, Rect::new(0, 0, 5, 1)),
+                (ok, Rect::new(0, 1, 4, 1)),
+                (cancel, Rect::new(5, 1, 6, 1)),
+            ],
+            tree.arrange(Rect::new(0, 0, 80, 24))
+        );
+
+        tree.set_name(ok, "ok");
+        assert_eq!(Some(ok), tree.find("ok"));
+        assert!(tree.remove(row));
+        assert_eq!(None, tree.find("ok"));
+        assert!(!tree.contains(ok) && !tree.contains(cancel));
+        assert_eq!(vec![title], tree.leaves());
+        assert!(!tree.remove(root));
+        // 叶子和已删除的节点不能作为父节点
+        assert_eq!(None, tree.add(title, Label::new("child")));
+        assert_eq!(None, tree.add_container(row, Layout::vertical(vec![])));
+        assert_eq!(2, tree.len());
+    }
+
+    #[test]
+    fn lookup_by_id_marks_dirty() {
+        let mut tree = WidgetTree::new(Layout::vertical(vec![]));
+        let root = tree.root();
+        let row = tree
+            .add_container(root, Layout::horizontal(vec![]))
+            .unwrap();
+        let check = tree.add(row, Checkbox::new("a")).unwrap();
+        let other = tree.add(root, Checkbox::new("b")).unwrap();
+        tree.clear_dirty();
+        assert!(!tree.is_dirty(check));
+
+        tree.get_mut::<Checkbox>(check).unwrap().checked = true;
+        assert!(tree.is_dirty(check) && !tree.is_dirty(other));
+        assert!(tree.get::<Checkbox>(check).unwrap().checked);
+        // 类型不对或者是容器时找不到
+        assert!(tree.get::<Button>(check).is_none());
+        assert!(tree.widget(row).is_none());
+        // 类型不对的 get_mut 不会引起重绘
+        tree.clear_dirty();
+        assert!(tree.get_mut::<Button>(other).is_none());
+        assert!(!tree.is_dirty(other));
+
+        tree.clear_dirty();
+        tree.mark_dirty(row);
+        assert!(tree.is_dirty(check) && !tree.is_dirty(other));
+    }
+}
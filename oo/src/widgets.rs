@@ -0,0 +1,41 @@
+// 常用组件：标签、输入框、复选框、选择框、列表、进度条和表格
+mod checkbox;
+mod label;
+mod list_view;
+mod progress_bar;
+mod select_box;
+mod table;
+mod text_input;
+
+pub use checkbox::Checkbox;
+pub use label::Label;
+pub use list_view::ListView;
+pub use progress_bar::ProgressBar;
+pub use select_box::SelectBox;
+pub use table::Table;
+pub use text_input::TextInput;
+
+// 按字符截断到 width 宽
+fn truncate(text: &str, width: u32) -> String {
+    text.chars().take(width as usize).collect()
+}
+
+// 在 [0, len) 中移动 index，结果限制在范围内
+fn step(index: usize, len: usize, up: bool) -> usize {
+    if up {
+        index.saturating_sub(1)
+    } else {
+        (index + 1).min(len.saturating_sub(1))
+    }
+}
+
+#[cfg(test)]
+fn row(term: &crate::terminal::Terminal, y: u32) -> String {
+    use crate::Canvas;
+    let (width, _) = term.size();
+    (0..width)
+        .map(|x| term.cell(x, y).map_or(' ', |c| c.ch))
+        .collect::<String>()
+        .trim_end()
+        .to_string()
+}
@@ -0,0 +1,81 @@
+use crate::canvas::{Canvas, Rect};
+use crate::{Draw, Event, Key, Style, Widget};
+
+type OnToggle = Box<dyn FnMut(bool)>;
+
+pub struct Checkbox {
+    pub label: String,
+    pub checked: bool,
+    pub disabled: bool,
+    focused: bool,
+    on_toggle: Option<OnToggle>,
+}
+
+impl Checkbox {
+    pub fn new(label: &str) -> Checkbox {
+        Checkbox {
+            label: String::from(label),
+            checked: false,
+            disabled: false,
+            focused: false,
+            on_toggle: None,
+        }
+    }
+
+    pub fn on_toggle(mut self, f: impl FnMut(bool) + 'static) -> Checkbox {
+        self.on_toggle = Some(Box::new(f));
+        self
+    }
+
+    pub fn toggle(&mut self) {
+        self.checked = !self.checked;
+        if let Some(f) = self.on_toggle.as_mut() {
+            f(self.checked);
+        }
+    }
+}
+
+impl Draw for Checkbox {
+    fn draw(&self, canvas: &mut dyn Canvas, area: Rect, style: &Style) {
+        let mark = if self.checked { 'x' } else { ' ' };
+        let text = format!("[{}] {}", mark, self.label);
+        canvas.draw_text(
+            area.x,
+            area.y,
+            &super::truncate(&text, area.width),
+            style.fg(),
+        );
+    }
+
+    fn size(&self) -> (u32, u32) {
+        (self.label.chars().count() as u32 + 4, 1)
+    }
+
+    fn kind(&self) -> &'static str {
+        "checkbox"
+    }
+}
+
+impl Widget for Checkbox {
+    fn handle_event(&mut self, event: &Event, area: Rect) -> bool {
+        match event {
+            Event::Click { x, y } if area.contains(*x, *y) => {}
+            Event::Key(Key::Enter) | Event::Key(Key::Char(' ')) if self.focused => {}
+            _ => return false,
+        }
+        self.toggle();
+        true
+    }
+
+    fn focusable(&self) -> bool {
+        true
+    }
+
+    fn set_focused(&mut self, focused: bool) {
+        self.focused = focused;
+    }
+
+    fn disabled(&self) -> bool {
+        self.disabled
+    }
+}
@@ -0,0 +1,45 @@
+use crate::canvas::{Canvas, Color, Rect};
+use crate::{Draw, Style, Widget};
+
+// 只读文字，可以有多行
+pub struct Label {
+    pub text: String,
+    // 设置后代替主题中的前景色
+    pub color: Option<Color>,
+}
+
+impl Label {
+    pub fn new(text: &str) -> Label {
+        Label {
+            text: String::from(text),
+            color: None,
+        }
+    }
+}
+
+impl Draw for Label {
+    fn draw(&self, canvas: &mut dyn Canvas, area: Rect, style: &Style) {
+        for (i, line) in self.text.lines().take(area.height as usize).enumerate() {
+            canvas.draw_text(
+                area.x,
+                area.y + i as u32,
+                &super::truncate(line, area.width),
+                self.color.unwrap_or(style.fg()),
+            );
+        }
+    }
+
+    fn size(&self) -> (u32, u32) {
+        let width = self.text.lines().map(|l| l.chars().count()).max();
+        (
+            width.unwrap_or(0) as u32,
+            self.text.lines().count().max(1) as u32,
+        )
+    }
+
+    fn kind(&self) -> &'static str {
+        "label"
+    }
+}
+
+impl Widget for Label {}
@@ -0,0 +1,236 @@
+use crate::canvas::{Canvas, Rect};
+use crate::{Draw, Event, Key, Style, Widget};
+
+type OnActivate = Box<dyn FnMut(usize, &str)>;
+
+// 可滚动的列表，offset 为第一行显示的项
+pub struct ListView {
+    pub width: u32,
+    pub height: u32,
+    items: Vec<String>,
+    selected: usize,
+    offset: usize,
+    pub disabled: bool,
+    focused: bool,
+    // 上一次点击的项，连续点击同一项时激活
+    last_click: Option<usize>,
+    on_activate: Option<OnActivate>,
+}
+
+impl ListView {
+    pub fn new(width: u32, height: u32, items: Vec<String>) -> ListView {
+        ListView {
+            width,
+            height,
+            items,
+            selected: 0,
+            offset: 0,
+            disabled: false,
+            focused: false,
+            last_click: None,
+            on_activate: None,
+        }
+    }
+
+    // 按回车或双击选中项时调用
+    pub fn on_activate(mut self, f: impl FnMut(usize, &str) + 'static) -> ListView {
+        self.on_activate = Some(Box::new(f));
+        self
+    }
+
+    pub fn items(&self) -> &[String] {
+        &self.items
+    }
+
+    pub fn push(&mut self, item: &str) {
+        self.items.push(String::from(item));
+    }
+
+    pub fn selected(&self) -> Option<usize> {
+        if self.items.is_empty() {
+            None
+        } else {
+            Some(self.selected)
+        }
+    }
+
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    // 高度为 height 的区域去掉边框后能显示的行数
+    fn rows(height: u32) -> usize {
+        height.saturating_sub(2) as usize
+    }
+
+    // 选中项移出可见范围时滚动，没有布局信息时按自身高度计算
+    pub fn select(&mut self, index: usize) {
+        self.select_in(index, ListView::rows(self.height));
+    }
+
+    fn select_in(&mut self, index: usize, rows: usize) {
+        if self.items.is_empty() {
+            return;
+        }
+        self.selected = index.min(self.items.len() - 1);
+        let rows = rows.max(1);
+        if self.selected < self.offset {
+            self.offset = self.selected;
+        } else if self.selected >= self.offset + rows {
+            self.offset = self.selected + 1 - rows;
+        }
+    }
+
+    fn activate(&mut self) {
+        if let Some(f) = self.on_activate.as_mut() {
+            if let Some(item) = self.items.get(self.selected) {
+                f(self.selected, item);
+            }
+        }
+    }
+}
+
+impl Draw for ListView {
+    fn draw(&self, canvas: &mut dyn Canvas, area: Rect, style: &Style) {
+        canvas.draw_box(area, style.border());
+        let rows = ListView::rows(area.height);
+        let width = area.width.saturating_sub(2);
+        let padding = style.padding().min(width / 2);
+        let visible = self.items.iter().enumerate().skip(self.offset).take(rows);
+        for (row, (i, item)) in visible.enumerate() {
+            let y = area.y + 1 + row as u32;
+            if i == self.selected {
+                canvas.fill_rect(Rect::new(area.x + 1, y, width, 1), style.accent());
+            }
+            let text = super::truncate(item, width - padding * 2);
+            canvas.draw_text(area.x + 1 + padding, y, &text, style.fg());
+        }
+        // 右边框上的箭头表示还有更多内容
+        let right = area.right().saturating_sub(1);
+        if self.offset > 0 {
+            canvas.draw_text(right, area.y + 1, "▲", style.border());
+        }
+        if self.offset + rows < self.items.len() {
+            canvas.draw_text(right, area.bottom().saturating_sub(2), "▼", style.border());
+        }
+    }
+
+    fn size(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    fn kind(&self) -> &'static str {
+        "list"
+    }
+}
+
+impl Widget for ListView {
+    fn handle_event(&mut self, event: &Event, area: Rect) -> bool {
+        let rows = ListView::rows(area.height);
+        match event {
+            Event::Click { x, y } if area.contains(*x, *y) => {
+                // 只有边框以内的行对应列表项
+                let row = match (*y - area.y).checked_sub(1) {
+                    Some(row) if (row as usize) < rows => row as usize,
+                    _ => return false,
+                };
+                let index = self.offset + row;
+                if index >= self.items.len() {
+                    return false;
+                }
+                // 连续两次点击同一项视为激活
+                if self.last_click == Some(index) {
+                    self.activate();
+                    self.last_click = None;
+                } else {
+                    self.last_click = Some(index);
+                }
+                self.select_in(index, rows);
+            }
+            Event::Key(key) if self.focused => {
+                self.last_click = None;
+                match key {
+                    Key::Up | Key::Down => self.select_in(
+                        super::step(self.selected, self.items.len(), *key == Key::Up),
+                        rows,
+                    ),
+                    Key::Home => self.select_in(0, rows),
+                    Key::End => self.select_in(self.items.len().saturating_sub(1), rows),
+                    Key::Enter => self.activate(),
+                    _ => return false,
+                }
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    fn focusable(&self) -> bool {
+        true
+    }
+
+    fn set_focused(&mut self, focused: bool) {
+        self.focused = focused;
+    }
+
+    fn disabled(&self) -> bool {
+        self.disabled
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::terminal::Terminal;
+
+    #[test]
+    fn scrolls_with_selection() {
+        let items = (1..=6).map(|i| format!("item {}", i)).collect();
+        let mut list = ListView::new(10, 5, items);
+        let area = Rect::new(0, 0, 10, 5);
+        list.set_focused(true);
+        for _ in 0..4 {
+            list.handle_event(&Event::Key(Key::Down), area);
+        }
+        assert_eq!(Some(4), list.selected());
+        assert_eq!(2, list.offset());
+
+        let mut term = Terminal::new(10, 5);
+        list.draw(&mut term, area, &Style::default());
+        assert_eq!("│item 3  ▲", super::super::row(&term, 1));
+        assert_eq!("│item 5  ▼", super::super::row(&term, 3));
+
+        list.handle_event(&Event::Key(Key::Home), area);
+        assert_eq!(0, list.offset());
+        list.handle_event(&Event::Click { x: 2, y: 3 }, area);
+        assert_eq!(Some(2), list.selected());
+        // 下边框不对应任何项
+        assert!(!list.handle_event(&Event::Click { x: 2, y: 4 }, area));
+        assert_eq!(Some(2), list.selected());
+    }
+
+    #[test]
+    fn second_click_activates() {
+        use std::cell::RefCell;
+        use std::rc::Rc;
+
+        let activated = Rc::new(RefCell::new(Vec::new()));
+        let log = Rc::clone(&activated);
+        let items = vec![String::from("a"), String::from("b")];
+        let mut list =
+            ListView::new(10, 4, items).on_activate(move |i, _| log.borrow_mut().push(i));
+        // 布局给的区域比 height 大时按实际区域计算行数
+        let area = Rect::new(0, 0, 10, 6);
+        let click = |y| Event::Click { x: 2, y };
+
+        // 第一次点击默认选中的第 0 项只是选中
+        list.handle_event(&click(1), area);
+        assert!(activated.borrow().is_empty());
+        list.handle_event(&click(1), area);
+        assert_eq!(vec![0], *activated.borrow());
+        list.handle_event(&click(2), area);
+        list.handle_event(&click(1), area);
+        assert_eq!(vec![0], *activated.borrow());
+        assert_eq!(Some(0), list.selected());
+    }
+}
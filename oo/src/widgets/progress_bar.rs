@@ -0,0 +1,48 @@
+use crate::canvas::{Canvas, Rect};
+use crate::{Draw, Style, Widget};
+
+pub struct ProgressBar {
+    pub width: u32,
+    // 0.0 到 1.0
+    value: f64,
+}
+
+impl ProgressBar {
+    pub fn new(width: u32) -> ProgressBar {
+        ProgressBar { width, value: 0.0 }
+    }
+
+    pub fn value(&self) -> f64 {
+        self.value
+    }
+
+    // 超出范围的值会被截断
+    pub fn set_value(&mut self, value: f64) {
+        self.value = value.clamp(0.0, 1.0);
+    }
+}
+
+impl Draw for ProgressBar {
+    fn draw(&self, canvas: &mut dyn Canvas, area: Rect, style: &Style) {
+        let filled = (area.width as f64 * self.value).round() as u32;
+        canvas.fill_rect(area, style.bg());
+        canvas.fill_rect(
+            Rect::new(area.x, area.y, filled, area.height),
+            style.accent(),
+        );
+        // 百分比居中显示
+        let text = format!("{}%", (self.value * 100.0).round());
+        let x = area.x + area.width.saturating_sub(text.len() as u32) / 2;
+        canvas.draw_text(x, area.y + area.height / 2, &text, style.fg());
+    }
+
+    fn size(&self) -> (u32, u32) {
+        (self.width, 1)
+    }
+
+    fn kind(&self) -> &'static str {
+        "progress"
+    }
+}
+
+impl Widget for ProgressBar {}
@@ -0,0 +1,212 @@
+use crate::canvas::{Canvas, Rect};
+use crate::{Draw, Event, Key, Style, Widget};
+
+type OnSelect = Box<dyn FnMut(usize, &str)>;
+
+// 单选框组，上下键移动光标，回车或空格选中；选项放不下时随光标滚动，offset 为第一行显示的项
+pub struct SelectBox {
+    pub width: u32,
+    pub height: u32,
+    options: Vec<String>,
+    cursor: usize,
+    offset: usize,
+    selected: Option<usize>,
+    pub disabled: bool,
+    focused: bool,
+    on_select: Option<OnSelect>,
+}
+
+impl SelectBox {
+    pub fn new(width: u32, height: u32, options: &[&str]) -> SelectBox {
+        SelectBox {
+            width,
+            height,
+            options: options.iter().map(|o| String::from(*o)).collect(),
+            cursor: 0,
+            offset: 0,
+            selected: None,
+            disabled: false,
+            focused: false,
+            on_select: None,
+        }
+    }
+
+    pub fn on_select(mut self, f: impl FnMut(usize, &str) + 'static) -> SelectBox {
+        self.on_select = Some(Box::new(f));
+        self
+    }
+
+    pub fn options(&self) -> &[String] {
+        &self.options
+    }
+
+    // 替换选项；原来选中的项不存在时取消选中，光标移到最后一项以内
+    pub fn set_options(&mut self, options: Vec<String>) {
+        self.options = options;
+        self.cursor = self.cursor.min(self.options.len().saturating_sub(1));
+        self.offset = self.offset.min(self.cursor);
+        if self.selected.is_some_and(|i| i >= self.options.len()) {
+            self.selected = None;
+        }
+    }
+
+    pub fn selected(&self) -> Option<usize> {
+        self.selected
+    }
+
+    pub fn selected_option(&self) -> Option<&str> {
+        self.selected
+            .and_then(|i| self.options.get(i))
+            .map(|o| o.as_str())
+    }
+
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    // 高度为 height 的区域去掉边框后能显示的行数
+    fn rows(height: u32) -> usize {
+        height.saturating_sub(2) as usize
+    }
+
+    // 光标移出可见范围时滚动
+    fn scroll_to_cursor(&mut self, rows: usize) {
+        let rows = rows.max(1);
+        if self.cursor < self.offset {
+            self.offset = self.cursor;
+        } else if self.cursor >= self.offset + rows {
+            self.offset = self.cursor + 1 - rows;
+        }
+    }
+
+    // 没有布局信息时按自身高度滚动
+    pub fn select(&mut self, index: usize) {
+        if index >= self.options.len() {
+            return;
+        }
+        self.cursor = index;
+        self.scroll_to_cursor(SelectBox::rows(self.height));
+        self.selected = Some(index);
+        if let Some(f) = self.on_select.as_mut() {
+            f(index, &self.options[index]);
+        }
+    }
+}
+
+impl Draw for SelectBox {
+    fn draw(&self, canvas: &mut dyn Canvas, area: Rect, style: &Style) {
+        canvas.draw_box(area, style.border());
+        let x = area.x + 1 + style.padding();
+        let width = area.width.saturating_sub(2 + style.padding() * 2);
+        let rows = SelectBox::rows(area.height);
+        let visible = self.options.iter().enumerate().skip(self.offset).take(rows);
+        for (row, (i, option)) in visible.enumerate() {
+            let y = area.y + 1 + row as u32;
+            let mark = if self.selected == Some(i) { '*' } else { ' ' };
+            // 焦点状态下光标所在的选项用强调色
+            let color = if self.focused && self.cursor == i {
+                style.accent()
+            } else {
+                style.fg()
+            };
+            let text = format!("({}) {}", mark, option);
+            canvas.draw_text(x, y, &super::truncate(&text, width), color);
+        }
+    }
+
+    fn size(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    fn kind(&self) -> &'static str {
+        "select"
+    }
+}
+
+impl Widget for SelectBox {
+    fn handle_event(&mut self, event: &Event, area: Rect) -> bool {
+        let rows = SelectBox::rows(area.height);
+        match event {
+            // 点击显示出来的选项所在的行，边框不算
+            Event::Click { x, y } if area.contains(*x, *y) => {
+                let row = match (*y - area.y).checked_sub(1) {
+                    Some(row) if (row as usize) < rows => row as usize,
+                    _ => return false,
+                };
+                let index = self.offset + row;
+                if index >= self.options.len() {
+                    return false;
+                }
+                self.select(index);
+            }
+            Event::Key(key) if self.focused => match key {
+                Key::Up | Key::Down => {
+                    self.cursor = super::step(self.cursor, self.options.len(), *key == Key::Up);
+                    self.scroll_to_cursor(rows);
+                }
+                Key::Enter | Key::Char(' ') => self.select(self.cursor),
+                _ => return false,
+            },
+            _ => return false,
+        }
+        true
+    }
+
+    fn focusable(&self) -> bool {
+        true
+    }
+
+    fn set_focused(&mut self, focused: bool) {
+        self.focused = focused;
+    }
+
+    fn disabled(&self) -> bool {
+        self.disabled
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shrinking_options_clamps_selection() {
+        let mut select = SelectBox::new(20, 5, &["a", "b", "c"]);
+        select.select(2);
+        assert_eq!(Some("c"), select.selected_option());
+
+        select.set_options(vec![String::from("x"), String::from("y")]);
+        assert_eq!(None, select.selected_option());
+        select.set_focused(true);
+        select.handle_event(&Event::Key(Key::Enter), Rect::new(0, 0, 20, 5));
+        assert_eq!(Some("y"), select.selected_option());
+
+        select.set_options(Vec::new());
+        assert_eq!(None, select.selected());
+        select.handle_event(&Event::Key(Key::Enter), Rect::new(0, 0, 20, 5));
+        assert_eq!(None, select.selected());
+    }
+
+    #[test]
+    fn clicks_and_keys_follow_the_visible_rows() {
+        // 高度 4 只能显示两项
+        let area = Rect::new(0, 0, 20, 4);
+        let mut select = SelectBox::new(20, 4, &["a", "b", "c", "d"]);
+        // 下边框那一行不对应任何选项
+        assert!(!select.handle_event(&Event::Click { x: 2, y: 3 }, area));
+        assert_eq!(None, select.selected());
+
+        select.set_focused(true);
+        for _ in 0..3 {
+            select.handle_event(&Event::Key(Key::Down), area);
+        }
+        assert_eq!(2, select.offset());
+        select.handle_event(&Event::Click { x: 2, y: 1 }, area);
+        assert_eq!(Some("c"), select.selected_option());
+
+        let mut term = crate::terminal::Terminal::new(20, 4);
+        select.draw(&mut term, area, &Style::default());
+        assert!(super::super::row(&term, 1).contains("(*) c"));
+        assert!(super::super::row(&term, 2).contains("( ) d"));
+    }
+}
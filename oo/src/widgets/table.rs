@@ -0,0 +1,166 @@
+use crate::canvas::{Canvas, Color, Rect};
+use crate::{Draw, Event, Key, Style, Widget};
+
+// 带表头的表格，列宽取表头和内容的最大宽度，放不下的部分截断
+pub struct Table {
+    pub width: u32,
+    pub height: u32,
+    headers: Vec<String>,
+    rows: Vec<Vec<String>>,
+    offset: usize,
+    pub disabled: bool,
+    focused: bool,
+}
+
+impl Table {
+    pub fn new(width: u32, height: u32, headers: &[&str]) -> Table {
+        Table {
+            width,
+            height,
+            headers: headers.iter().map(|h| String::from(*h)).collect(),
+            rows: Vec::new(),
+            offset: 0,
+            disabled: false,
+            focused: false,
+        }
+    }
+
+    // 缺少的单元格补空，多出的忽略
+    pub fn add_row(&mut self, cells: &[&str]) {
+        let row = (0..self.headers.len())
+            .map(|i| String::from(cells.get(i).copied().unwrap_or("")))
+            .collect();
+        self.rows.push(row);
+    }
+
+    pub fn rows(&self) -> &[Vec<String>] {
+        &self.rows
+    }
+
+    fn column_widths(&self) -> Vec<u32> {
+        let mut widths: Vec<u32> = self
+            .headers
+            .iter()
+            .map(|h| h.chars().count() as u32)
+            .collect();
+        for row in &self.rows {
+            for (w, cell) in widths.iter_mut().zip(row) {
+                *w = (*w).max(cell.chars().count() as u32);
+            }
+        }
+        widths
+    }
+
+    // 表头和分隔线之外能显示的行数
+    fn visible_rows(height: u32) -> usize {
+        height.saturating_sub(2) as usize
+    }
+
+    fn draw_row(
+        canvas: &mut dyn Canvas,
+        area: Rect,
+        y: u32,
+        cells: &[String],
+        widths: &[u32],
+        color: Color,
+    ) {
+        let mut x = area.x;
+        for (cell, width) in cells.iter().zip(widths) {
+            if x >= area.right() {
+                break;
+            }
+            let width = (*width).min(area.right() - x);
+            canvas.draw_text(x, y, &super::truncate(cell, width), color);
+            // 列之间空一格
+            x += width + 1;
+        }
+    }
+}
+
+impl Draw for Table {
+    fn draw(&self, canvas: &mut dyn Canvas, area: Rect, style: &Style) {
+        if area.height == 0 {
+            return;
+        }
+        let widths = self.column_widths();
+        Table::draw_row(canvas, area, area.y, &self.headers, &widths, style.fg());
+        if area.height > 1 {
+            let line = "─".repeat(area.width as usize);
+            canvas.draw_text(area.x, area.y + 1, &line, style.border());
+        }
+        let visible = self.rows.iter().skip(self.offset);
+        for (i, row) in visible.take(Table::visible_rows(area.height)).enumerate() {
+            let y = area.y + 2 + i as u32;
+            Table::draw_row(canvas, area, y, row, &widths, style.fg());
+        }
+    }
+
+    fn size(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    fn kind(&self) -> &'static str {
+        "table"
+    }
+}
+
+impl Widget for Table {
+    fn handle_event(&mut self, event: &Event, area: Rect) -> bool {
+        if !self.focused {
+            return false;
+        }
+        let max = self
+            .rows
+            .len()
+            .saturating_sub(Table::visible_rows(area.height));
+        match event {
+            Event::Key(Key::Up) => self.offset = self.offset.saturating_sub(1),
+            Event::Key(Key::Down) => self.offset = (self.offset + 1).min(max),
+            Event::Key(Key::Home) => self.offset = 0,
+            Event::Key(Key::End) => self.offset = max,
+            _ => return false,
+        }
+        true
+    }
+
+    fn focusable(&self) -> bool {
+        true
+    }
+
+    fn set_focused(&mut self, focused: bool) {
+        self.focused = focused;
+    }
+
+    fn disabled(&self) -> bool {
+        self.disabled
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::terminal::Terminal;
+
+    #[test]
+    fn draws_aligned_columns() {
+        let mut table = Table::new(16, 4, &["name", "qty"]);
+        table.add_row(&["apple", "3"]);
+        table.add_row(&["kiwi", "12", "extra"]);
+        table.add_row(&["fig"]);
+        let area = Rect::new(0, 0, 12, 4);
+        let mut term = Terminal::new(12, 4);
+        table.draw(&mut term, area, &Style::default());
+        let rows: Vec<String> = (0..4).map(|y| super::super::row(&term, y)).collect();
+        assert_eq!(
+            vec!["name  qty", "────────────", "apple 3", "kiwi  12"],
+            rows
+        );
+
+        table.set_focused(true);
+        table.handle_event(&Event::Key(Key::End), area);
+        term.clear();
+        table.draw(&mut term, area, &Style::default());
+        assert_eq!("kiwi  12", super::super::row(&term, 2));
+        assert_eq!("fig", super::super::row(&term, 3));
+    }
+}
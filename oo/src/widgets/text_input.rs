@@ -0,0 +1,201 @@
+use crate::canvas::{Canvas, Rect};
+use crate::{Draw, Event, Key, Style, Widget};
+
+type OnSubmit = Box<dyn FnMut(&str)>;
+
+// 单行输入框，cursor 为光标前的字符数
+pub struct TextInput {
+    pub width: u32,
+    value: String,
+    cursor: usize,
+    pub disabled: bool,
+    focused: bool,
+    on_submit: Option<OnSubmit>,
+}
+
+impl TextInput {
+    pub fn new(width: u32) -> TextInput {
+        TextInput {
+            width,
+            value: String::new(),
+            cursor: 0,
+            disabled: false,
+            focused: false,
+            on_submit: None,
+        }
+    }
+
+    // 按回车时调用
+    pub fn on_submit(mut self, f: impl FnMut(&str) + 'static) -> TextInput {
+        self.on_submit = Some(Box::new(f));
+        self
+    }
+
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    // 设置内容，光标移到末尾
+    pub fn set_value(&mut self, value: &str) {
+        self.value = String::from(value);
+        self.cursor = self.len();
+    }
+
+    fn len(&self) -> usize {
+        self.value.chars().count()
+    }
+
+    // 第 index 个字符的字节位置
+    fn byte_offset(&self, index: usize) -> usize {
+        self.value
+            .char_indices()
+            .nth(index)
+            .map_or(self.value.len(), |(i, _)| i)
+    }
+
+    pub fn insert(&mut self, c: char) {
+        let offset = self.byte_offset(self.cursor);
+        self.value.insert(offset, c);
+        self.cursor += 1;
+    }
+
+    pub fn backspace(&mut self) {
+        if self.cursor > 0 {
+            self.cursor -= 1;
+            let offset = self.byte_offset(self.cursor);
+            self.value.remove(offset);
+        }
+    }
+
+    pub fn delete(&mut self) {
+        if self.cursor < self.len() {
+            let offset = self.byte_offset(self.cursor);
+            self.value.remove(offset);
+        }
+    }
+
+    // 输入框内能显示的字符数（去掉边框）
+    fn visible(width: u32) -> usize {
+        width.saturating_sub(2) as usize
+    }
+
+    // 内容超出时向左滚动，保证光标可见
+    fn scroll(&self, width: u32) -> usize {
+        let visible = TextInput::visible(width);
+        (self.cursor + 1).saturating_sub(visible)
+    }
+}
+
+impl Draw for TextInput {
+    fn draw(&self, canvas: &mut dyn Canvas, area: Rect, style: &Style) {
+        canvas.draw_box(area, style.border());
+        let scroll = self.scroll(area.width);
+        let visible = TextInput::visible(area.width);
+        let text: String = self.value.chars().skip(scroll).take(visible).collect();
+        let y = area.y + area.height / 2;
+        canvas.draw_text(area.x + 1, y, &text, style.fg());
+        if self.focused && visible > 0 {
+            let x = area.x + 1 + (self.cursor - scroll) as u32;
+            let under = self.value.chars().nth(self.cursor).unwrap_or(' ');
+            canvas.fill_rect(Rect::new(x, y, 1, 1), style.accent());
+            canvas.draw_text(x, y, &under.to_string(), style.fg());
+        }
+    }
+
+    fn size(&self) -> (u32, u32) {
+        (self.width, 3)
+    }
+
+    fn kind(&self) -> &'static str {
+        "input"
+    }
+}
+
+impl Widget for TextInput {
+    fn handle_event(&mut self, event: &Event, area: Rect) -> bool {
+        if let Event::Click { x, y } = event {
+            if !area.contains(*x, *y) {
+                return false;
+            }
+            // 点击的位置对应的字符
+            let column = x.saturating_sub(area.x + 1) as usize;
+            self.cursor = (self.scroll(area.width) + column).min(self.len());
+            return true;
+        }
+        if !self.focused {
+            return false;
+        }
+        match event {
+            Event::Key(Key::Char(c)) => self.insert(*c),
+            Event::Key(Key::Backspace) => self.backspace(),
+            Event::Key(Key::Delete) => self.delete(),
+            Event::Key(Key::Left) => self.cursor = self.cursor.saturating_sub(1),
+            Event::Key(Key::Right) => self.cursor = (self.cursor + 1).min(self.len()),
+            Event::Key(Key::Home) => self.cursor = 0,
+            Event::Key(Key::End) => self.cursor = self.len(),
+            Event::Key(Key::Enter) => {
+                if let Some(f) = self.on_submit.as_mut() {
+                    f(&self.value);
+                }
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    fn focusable(&self) -> bool {
+        true
+    }
+
+    fn set_focused(&mut self, focused: bool) {
+        self.focused = focused;
+    }
+
+    fn disabled(&self) -> bool {
+        self.disabled
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::terminal::Terminal;
+
+    fn key(input: &mut TextInput, key: Key) {
+        input.handle_event(&Event::Key(key), Rect::new(0, 0, 6, 3));
+    }
+
+    #[test]
+    fn edits_at_cursor() {
+        let mut input = TextInput::new(6);
+        input.set_focused(true);
+        for c in "héllo".chars() {
+            key(&mut input, Key::Char(c));
+        }
+        key(&mut input, Key::Home);
+        key(&mut input, Key::Delete);
+        key(&mut input, Key::End);
+        key(&mut input, Key::Left);
+        key(&mut input, Key::Backspace);
+        key(&mut input, Key::Char('L'));
+        assert_eq!("élLo", input.value());
+        assert_eq!(3, input.cursor());
+    }
+
+    #[test]
+    fn scrolls_to_keep_cursor_visible() {
+        let mut input = TextInput::new(6);
+        input.set_value("abcdefgh");
+        let mut term = Terminal::new(6, 3);
+        input.draw(&mut term, Rect::new(0, 0, 6, 3), &Style::default());
+        assert_eq!("│fgh │", super::super::row(&term, 1));
+
+        // 点击第一个可见字符，光标移到 f 之前
+        input.handle_event(&Event::Click { x: 1, y: 1 }, Rect::new(0, 0, 6, 3));
+        assert_eq!(5, input.cursor());
+    }
+}
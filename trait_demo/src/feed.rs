@@ -0,0 +1,282 @@
+// 信息流：保存带时间戳的各种 Summary，按时间倒序排列
+use crate::{Summary, SummaryOptions};
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+pub struct Entry {
+    // Unix 时间戳，单位秒
+    pub timestamp: u64,
+    pub item: Box<dyn Summary>,
+}
+
+impl Entry {
+    // 作者不区分是否带 @
+    pub fn is_by(&self, author: &str) -> bool {
+        let own = self.item.summarize_author();
+        own.trim_start_matches('@') == author.trim_start_matches('@')
+    }
+}
+
+pub struct Feed {
+    pub name: String,
+    // 新的在前
+    entries: Vec<Entry>,
+}
+
+impl Feed {
+    pub fn new(name: &str) -> Feed {
+        Feed {
+            name: String::from(name),
+            entries: Vec::new(),
+        }
+    }
+
+    pub fn push(&mut self, timestamp: u64, item: impl Summary + 'static) {
+        self.push_boxed(timestamp, Box::new(item));
+    }
+
+    // 时间相同时先加入的排在前面
+    pub fn push_boxed(&mut self, timestamp: u64, item: Box<dyn Summary>) {
+        let i = self.entries.partition_point(|e| e.timestamp >= timestamp);
+        self.entries.insert(i, Entry { timestamp, item });
+    }
+
+    pub fn entries(&self) -> &[Entry] {
+        &self.entries
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn timeline(&self) -> Timeline<'_> {
+        Timeline {
+            entries: self.entries.iter().collect(),
+        }
+    }
+}
+
+// 归并时堆里的元素：各个来源当前的第一条
+struct Head<'a> {
+    entry: &'a Entry,
+    source: usize,
+    index: usize,
+}
+
+impl PartialEq for Head<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Head<'_> {}
+
+impl PartialOrd for Head<'_> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+// 时间新的优先，相同时靠前的来源优先
+impl Ord for Head<'_> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.entry
+            .timestamp
+            .cmp(&other.entry.timestamp)
+            .then(other.source.cmp(&self.source))
+    }
+}
+
+// 按时间倒序排列的条目引用，可以来自多个 Feed
+pub struct Timeline<'a> {
+    entries: Vec<&'a Entry>,
+}
+
+impl<'a> Timeline<'a> {
+    // 每个来源都已经有序，用堆做多路归并
+    pub fn merge(feeds: &[&'a Feed]) -> Timeline<'a> {
+        let mut heap = BinaryHeap::new();
+        for (source, feed) in feeds.iter().enumerate() {
+            if let Some(entry) = feed.entries.first() {
+                heap.push(Head {
+                    entry,
+                    source,
+                    index: 0,
+                });
+            }
+        }
+        let mut entries = Vec::with_capacity(feeds.iter().map(|f| f.len()).sum());
+        while let Some(head) = heap.pop() {
+            entries.push(head.entry);
+            if let Some(entry) = feeds[head.source].entries.get(head.index + 1) {
+                heap.push(Head {
+                    entry,
+                    source: head.source,
+                    index: head.index + 1,
+                });
+            }
+        }
+        Timeline { entries }
+    }
+
+    pub fn entries(&self) -> &[&'a Entry] {
+        &self.entries
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn by_author(&self, author: &str) -> Timeline<'a> {
+        Timeline {
+            entries: self
+                .entries
+                .iter()
+                .copied()
+                .filter(|e| e.is_by(author))
+                .collect(),
+        }
+    }
+
+    // 第 page 页，从 0 开始；超出范围时为空
+    pub fn page(&self, page: usize, per_page: usize) -> &[&'a Entry] {
+        let start = page.saturating_mul(per_page).min(self.entries.len());
+        let end = start.saturating_add(per_page).min(self.entries.len());
+        &self.entries[start..end]
+    }
+
+    pub fn pages(&self, per_page: usize) -> usize {
+        if per_page == 0 {
+            return 0;
+        }
+        self.entries.len().div_ceil(per_page)
+    }
+
+    // 纯文本摘要，每条一行
+    pub fn digest(&self, title: &str) -> String {
+        self.render_digest(title, |item| item.summarize())
+    }
+
+    // 按选项生成每条的摘要，例如限制长度
+    pub fn digest_with(&self, title: &str, options: &SummaryOptions) -> String {
+        self.render_digest(title, |item| item.summarize_with(options))
+    }
+
+    fn render_digest(&self, title: &str, summarize: impl Fn(&dyn Summary) -> String) -> String {
+        let mut out = format!("{}\n{}\n", title, "=".repeat(title.chars().count()));
+        if self.entries.is_empty() {
+            out.push_str("(nothing new)\n");
+        }
+        for entry in &self.entries {
+            out.push_str(&format!(
+                "- {} {}\n",
+                format_timestamp(entry.timestamp),
+                summarize(entry.item.as_ref())
+            ));
+        }
+        out
+    }
+}
+
+// UTC 时间，格式为 YYYY-MM-DD HH:MM
+pub fn format_timestamp(timestamp: u64) -> String {
+    let days = (timestamp / 86400) as i64;
+    let seconds = timestamp % 86400;
+    // 由天数计算公历日期，算法来自 Howard Hinnant 的 civil_from_days
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}",
+        year,
+        month,
+        day,
+        seconds / 3600,
+        seconds % 3600 / 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{NewArticle, Tweet};
+
+    fn tweet(username: &str, content: &str) -> Tweet {
+        Tweet {
+            username: String::from(username),
+            content: String::from(content),
+            reply: false,
+            retweet: false,
+        }
+    }
+
+    fn article(author: &str) -> NewArticle {
+        NewArticle {
+            headline: String::from("headline"),
+            location: String::from("location"),
+            author: String::from(author),
+            content: String::from("content"),
+        }
+    }
+
+    #[test]
+    fn merges_sources_newest_first() {
+        let mut tweets = Feed::new("tweets");
+        tweets.push(300, tweet("a", "third"));
+        tweets.push(100, tweet("a", "first"));
+        tweets.push(200, tweet("b", "second"));
+        let mut news = Feed::new("news");
+        news.push(250, article("c"));
+        news.push(200, article("d"));
+
+        let timeline = Timeline::merge(&[&tweets, &news]);
+        let stamps: Vec<u64> = timeline.entries().iter().map(|e| e.timestamp).collect();
+        assert_eq!(vec![300, 250, 200, 200, 100], stamps);
+        // 时间相同时先来自第一个来源
+        assert_eq!("b: second", timeline.entries()[2].item.summarize());
+
+        let by_a = timeline.by_author("@a");
+        assert_eq!(2, by_a.len());
+        assert_eq!(1, timeline.by_author("d").len());
+
+        assert_eq!(3, timeline.pages(2));
+        assert_eq!(1, timeline.page(2, 2).len());
+        assert!(timeline.page(5, 2).is_empty());
+    }
+
+    #[test]
+    fn renders_digest() {
+        let mut feed = Feed::new("tweets");
+        feed.push(1_700_000_000, tweet("horse", "hello"));
+        assert_eq!(
+            "Daily\n=====\n- 2023-11-14 22:13 horse: hello\n",
+            feed.timeline().digest("Daily")
+        );
+        assert_eq!("1970-01-01 00:00", format_timestamp(0));
+        assert_eq!("2000-02-29 12:30", format_timestamp(951_827_400));
+        // 长度限制包括用户名
+        let options = SummaryOptions::new().max_chars(10);
+        assert_eq!(
+            "Daily\n=====\n- 2023-11-14 22:13 horse: he…\n",
+            feed.timeline().digest_with("Daily", &options)
+        );
+        assert!(Feed::new("x")
+            .timeline()
+            .digest("x")
+            .contains("nothing new"));
+    }
+}
@@ -0,0 +1,383 @@
+// 从 RSS 2.0、Atom 和 JSON Feed 文件读取 NewArticle
+// 单个条目有问题时跳过并记录警告，只有整个文档无法识别时才返回错误
+use crate::json::{self, Json, JsonError};
+use crate::xml::{self, Element, XmlError};
+use crate::NewArticle;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+#[derive(Debug)]
+pub enum IngestError {
+    Io(io::Error),
+    Xml(XmlError),
+    Json(JsonError),
+    // 能解析但不是认识的订阅源格式
+    UnknownFormat(String),
+}
+
+impl fmt::Display for IngestError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            IngestError::Io(e) => write!(f, "io error: {}", e),
+            IngestError::Xml(e) => write!(f, "malformed XML {}", e),
+            IngestError::Json(e) => write!(f, "malformed JSON {}", e),
+            IngestError::UnknownFormat(what) => write!(f, "not a feed: {}", what),
+        }
+    }
+}
+
+impl std::error::Error for IngestError {}
+
+impl From<io::Error> for IngestError {
+    fn from(e: io::Error) -> Self {
+        IngestError::Io(e)
+    }
+}
+
+impl From<XmlError> for IngestError {
+    fn from(e: XmlError) -> Self {
+        IngestError::Xml(e)
+    }
+}
+
+impl From<JsonError> for IngestError {
+    fn from(e: JsonError) -> Self {
+        IngestError::Json(e)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Rss,
+    Atom,
+    JsonFeed,
+}
+
+pub struct Ingested {
+    pub format: Format,
+    // 订阅源自己的标题
+    pub title: String,
+    pub articles: Vec<NewArticle>,
+    // 被跳过的条目和原因
+    pub warnings: Vec<String>,
+}
+
+impl Ingested {
+    fn new(format: Format, title: Option<String>) -> Ingested {
+        Ingested {
+            format,
+            title: title.unwrap_or_default(),
+            articles: Vec::new(),
+            warnings: Vec::new(),
+        }
+    }
+
+    // 没有标题的条目无法生成摘要，跳过
+    fn push(
+        &mut self,
+        index: usize,
+        headline: Option<String>,
+        author: Option<String>,
+        location: Option<String>,
+        content: Option<String>,
+    ) {
+        match headline {
+            Some(headline) => self.articles.push(NewArticle {
+                headline,
+                author: author.unwrap_or_else(|| self.title.clone()),
+                location: location.unwrap_or_default(),
+                content: content.unwrap_or_default(),
+            }),
+            None => self
+                .warnings
+                .push(format!("item {}: missing title, skipped", index + 1)),
+        }
+    }
+}
+
+// 根据文件内容判断格式
+pub fn parse(text: &str) -> Result<Ingested, IngestError> {
+    let trimmed = text.trim_start_matches('\u{feff}').trim_start();
+    if trimmed.starts_with('{') {
+        return parse_json_feed(trimmed);
+    }
+    let root = xml::parse(trimmed)?;
+    match root.name.as_str() {
+        "rss" | "rdf:RDF" => Ok(from_rss(&root)),
+        "feed" => Ok(from_atom(&root)),
+        _ => Err(unexpected_root(&root)),
+    }
+}
+
+fn unexpected_root(root: &Element) -> IngestError {
+    IngestError::UnknownFormat(format!("unexpected root element <{}>", root.name))
+}
+
+// 不是 UTF-8 的文件（如 ISO-8859-1 编码的 RSS）也尽量读取，无法识别的字节换成 U+FFFD
+pub fn load(path: impl AsRef<Path>) -> Result<Ingested, IngestError> {
+    let bytes = fs::read(path)?;
+    let text = String::from_utf8_lossy(&bytes);
+    let mut feed = parse(&text)?;
+    if std::str::from_utf8(&bytes).is_err() {
+        feed.warnings.insert(
+            0,
+            String::from("file is not valid UTF-8, invalid bytes replaced"),
+        );
+    }
+    Ok(feed)
+}
+
+pub fn parse_rss(text: &str) -> Result<Ingested, IngestError> {
+    let root = xml::parse(text)?;
+    match root.name.as_str() {
+        "rss" | "rdf:RDF" => Ok(from_rss(&root)),
+        _ => Err(unexpected_root(&root)),
+    }
+}
+
+pub fn parse_atom(text: &str) -> Result<Ingested, IngestError> {
+    let root = xml::parse(text)?;
+    match root.name.as_str() {
+        "feed" => Ok(from_atom(&root)),
+        _ => Err(unexpected_root(&root)),
+    }
+}
+
+// RSS 2.0 的条目在 <channel> 里，RSS 1.0 (RDF) 的条目和 <channel> 同级
+fn from_rss(root: &Element) -> Ingested {
+    let channel = root.child("channel");
+    let title = channel.and_then(|c| c.child_text("title"));
+    let mut out = Ingested::new(Format::Rss, title);
+    let items = channel
+        .into_iter()
+        .flat_map(|c| c.children_named("item"))
+        .chain(root.children_named("item"));
+    for (i, item) in items.enumerate() {
+        let author = item
+            .child_text("dc:creator")
+            .or_else(|| item.child_text("author"));
+        let content = item
+            .child_text("content:encoded")
+            .or_else(|| item.child_text("description"));
+        let link = item.child_text("link").or_else(|| item.child_text("guid"));
+        out.push(i, item.child_text("title"), author, link, content);
+    }
+    out
+}
+
+fn atom_author(element: &Element) -> Option<String> {
+    element.child("author").and_then(|a| a.child_text("name"))
+}
+
+// 优先使用 rel="alternate" 或没有 rel 的链接
+fn atom_link(entry: &Element) -> Option<String> {
+    let links: Vec<&Element> = entry.children_named("link").collect();
+    links
+        .iter()
+        .find(|l| matches!(l.attr("rel"), None | Some("alternate")))
+        .or(links.first())
+        .and_then(|l| l.attr("href"))
+        .map(String::from)
+}
+
+fn from_atom(root: &Element) -> Ingested {
+    let mut out = Ingested::new(Format::Atom, root.child_text("title"));
+    let feed_author = atom_author(root);
+    for (i, entry) in root.children_named("entry").enumerate() {
+        let author = atom_author(entry).or_else(|| feed_author.clone());
+        let content = entry
+            .child_text("content")
+            .or_else(|| entry.child_text("summary"));
+        out.push(
+            i,
+            entry.child_text("title"),
+            author,
+            atom_link(entry),
+            content,
+        );
+    }
+    out
+}
+
+// 1.1 版用 authors 数组，1.0 版用 author 对象
+fn json_author(value: &Json) -> Option<String> {
+    value
+        .get("authors")
+        .and_then(Json::as_array)
+        .and_then(|a| a.first())
+        .or_else(|| value.get("author"))
+        .and_then(|a| a.str_field("name"))
+        .map(String::from)
+}
+
+pub fn parse_json_feed(text: &str) -> Result<Ingested, IngestError> {
+    let root = json::parse(text)?;
+    let is_feed = root
+        .str_field("version")
+        .is_some_and(|v| v.starts_with("https://jsonfeed.org/version/"));
+    if !is_feed {
+        return Err(IngestError::UnknownFormat(String::from(
+            "missing JSON Feed version",
+        )));
+    }
+    let title = root.str_field("title").map(String::from);
+    let mut out = Ingested::new(Format::JsonFeed, title);
+    let feed_author = json_author(&root);
+    let items = match root.get("items").and_then(Json::as_array) {
+        Some(items) => items,
+        None => {
+            out.warnings.push(String::from("missing items array"));
+            return Ok(out);
+        }
+    };
+    for (i, item) in items.iter().enumerate() {
+        if !matches!(item, Json::Object(_)) {
+            out.warnings
+                .push(format!("item {}: not an object, skipped", i + 1));
+            continue;
+        }
+        let field = |key| item.str_field(key).map(String::from);
+        let author = json_author(item).or_else(|| feed_author.clone());
+        let content = field("content_text")
+            .or_else(|| field("content_html"))
+            .or_else(|| field("summary"));
+        let location = field("url").or_else(|| field("external_url"));
+        out.push(i, field("title"), author, location, content);
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_rss() {
+        let text = r#"<?xml version="1.0"?>
+<rss version="2.0" xmlns:dc="http://purl.org/dc/elements/1.1/">
+  <channel>
+    <title>Example News</title>
+    <item>
+      <title>First &amp; foremost</title>
+      <link>https://example.com/1</link>
+      <dc:creator>Alice</dc:creator>
+      <description><![CDATA[<p>Hello</p>]]></description>
+    </item>
+    <item><description>no title</description></item>
+    <item><title>Second</title>
+  </channel>
+</rss>"#;
+        let feed = parse(text).unwrap();
+        assert_eq!(Format::Rss, feed.format);
+        assert_eq!(2, feed.articles.len());
+        let first = &feed.articles[0];
+        assert_eq!("First & foremost", first.headline);
+        assert_eq!("Alice", first.author);
+        assert_eq!("https://example.com/1", first.location);
+        assert_eq!("<p>Hello</p>", first.content);
+        // 没有作者时使用订阅源标题，未关闭的 <item> 仍然被读出
+        assert_eq!("Example News", feed.articles[1].author);
+        assert_eq!(vec!["item 2: missing title, skipped"], feed.warnings);
+    }
+
+    #[test]
+    fn reads_atom() {
+        let text = r#"<feed xmlns="http://www.w3.org/2005/Atom">
+  <title>Blog</title>
+  <author><name>Bob</name></author>
+  <entry>
+    <title>Post</title>
+    <link rel="self" href="https://example.com/self"/>
+    <link href="https://example.com/post"/>
+    <summary>Short</summary>
+  </entry>
+</feed>"#;
+        let feed = parse(text).unwrap();
+        assert_eq!(Format::Atom, feed.format);
+        let post = &feed.articles[0];
+        assert_eq!(
+            ("Post", "Bob", "https://example.com/post", "Short"),
+            (
+                post.headline.as_str(),
+                post.author.as_str(),
+                post.location.as_str(),
+                post.content.as_str()
+            )
+        );
+    }
+
+    #[test]
+    fn reads_json_feed() {
+        let text = r#"{
+            "version": "https://jsonfeed.org/version/1.1",
+            "title": "JSON Blog",
+            "authors": [{"name": "Carol"}],
+            "items": [
+                {"id": "1", "title": "Hi", "url": "https://example.com/hi", "content_text": "Text"},
+                {"id": "2", "author": {"name": "Dan"}, "title": "Old style", "content_html": "<b>x</b>"},
+                42
+            ]
+        }"#;
+        let feed = parse(text).unwrap();
+        assert_eq!(Format::JsonFeed, feed.format);
+        assert_eq!("JSON Blog", feed.title);
+        assert_eq!("Carol", feed.articles[0].author);
+        assert_eq!("Dan", feed.articles[1].author);
+        assert_eq!("<b>x</b>", feed.articles[1].content);
+        assert_eq!(vec!["item 3: not an object, skipped"], feed.warnings);
+    }
+
+    #[test]
+    fn reports_documents_that_are_not_feeds() {
+        let message = |text| match parse(text) {
+            Ok(_) => String::from("ok"),
+            Err(e) => e.to_string(),
+        };
+        assert_eq!(
+            "not a feed: unexpected root element <html>",
+            message("<html><body/></html>")
+        );
+        assert_eq!(
+            "not a feed: missing JSON Feed version",
+            message(r#"{"items": []}"#)
+        );
+        assert_eq!(
+            "malformed JSON at byte 11: unexpected end of input",
+            message(r#"{"version":"#)
+        );
+        assert_eq!("malformed XML at byte 0: no root element", message("hello"));
+        assert_eq!(
+            "not a feed: unexpected root element <feed>",
+            parse_rss("<feed><entry/></feed>")
+                .map(|_| ())
+                .unwrap_err()
+                .to_string()
+        );
+        assert_eq!(
+            "not a feed: unexpected root element <rss>",
+            parse_atom("<rss/>").map(|_| ()).unwrap_err().to_string()
+        );
+    }
+
+    #[test]
+    fn loads_files_that_are_not_utf8() {
+        let path =
+            std::env::temp_dir().join(format!("trait_demo_ingest_{}.xml", std::process::id()));
+        // ISO-8859-1 编码的 "Café"
+        let mut bytes =
+            b"<?xml version=\"1.0\" encoding=\"ISO-8859-1\"?><rss><channel><item><title>Caf"
+                .to_vec();
+        bytes.push(0xE9);
+        bytes.extend_from_slice(b"</title></item></channel></rss>");
+        fs::write(&path, bytes).unwrap();
+        let feed = load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!("Caf\u{fffd}", feed.articles[0].headline);
+        assert_eq!(
+            vec!["file is not valid UTF-8, invalid bytes replaced"],
+            feed.warnings
+        );
+    }
+}
@@ -1,9 +1,25 @@
+pub mod feed;
+pub mod ingest;
+pub mod json;
+mod marks;
+pub mod notify;
+pub mod summary;
+pub mod xml;
+
+pub use notify::{Notifier, NotifyError};
+pub use summary::SummaryOptions;
+
 pub trait Summary {
     fn summarize_author(&self) -> String;
 
     fn summarize(&self) -> String {
         format!("(Read more from {} ...)", self.summarize_author())
     }
+
+    // 按选项处理后的摘要，默认处理 summarize() 的结果
+    fn summarize_with(&self, options: &SummaryOptions) -> String {
+        options.apply(&self.summarize())
+    }
 }
 
 pub struct NewArticle {
@@ -20,6 +36,11 @@ impl Summary for NewArticle {
     fn summarize_author(&self) -> String {
         format!("@{}", self.author)
     }
+
+    // 去掉 HTML 和取第一句只处理正文，长度限制包括标题
+    fn summarize_with(&self, options: &SummaryOptions) -> String {
+        options.apply_prefixed(&format!("{}: ", self.headline), &self.content)
+    }
 }
 
 pub struct Tweet {
@@ -36,37 +57,69 @@ impl Summary for Tweet {
     fn summarize_author(&self) -> String {
         format!("@{}", self.username)
     }
+
+    fn summarize_with(&self, options: &SummaryOptions) -> String {
+        options.apply_prefixed(&format!("{}: ", self.username), &self.content)
+    }
 }
 
-pub fn notify(item: impl Summary) {
-    println!("Breaking news! {}", item.summarize());
+pub fn notify(
+    item: impl Summary,
+    notifier: &mut (impl Notifier + ?Sized),
+) -> Result<(), NotifyError> {
+    notifier.send(&format!("Breaking news! {}", item.summarize()))
 }
 
-pub fn notify_bound<T: Summary>(item: T) {
-    println!("Breaking news! {}", item.summarize());
+pub fn notify_bound<T: Summary, N: Notifier + ?Sized>(
+    item: T,
+    notifier: &mut N,
+) -> Result<(), NotifyError> {
+    notifier.send(&format!("Breaking news! {}", item.summarize()))
 }
 
-pub fn notify_mix(item: impl Summary + std::fmt::Display) {
-    println!("Breaking news! {}", item.summarize());
+// 也可以传入 &mut dyn Notifier
+pub fn notify_mix(
+    item: impl Summary + std::fmt::Display,
+    notifier: &mut (impl Notifier + ?Sized),
+) -> Result<(), NotifyError> {
+    notifier.send(&format!("Breaking news! {}", item.summarize()))
 }
 
-pub fn notify_bound_mix<T: Summary + std::fmt::Display>(item: T) {
-    println!("Breaking news! {}", item.summarize());
+pub fn notify_bound_mix<T: Summary + std::fmt::Display, N: Notifier + ?Sized>(
+    item: T,
+    notifier: &mut N,
+) -> Result<(), NotifyError> {
+    notifier.send(&format!("Breaking news! {}", item.summarize()))
 }
 
-pub fn notify_bound_mulit_mix<T: Summary + std::fmt::Display, U: Clone + core::fmt::Debug>(
+// 发送成功时返回发出的消息
+pub fn notify_bound_mulit_mix<
+    T: Summary + std::fmt::Display,
+    U: Clone + core::fmt::Debug,
+    N: Notifier + ?Sized,
+>(
     a: T,
     _b: U,
-) -> String {
-    format!("Breaking news! {}", a.summarize())
+    notifier: &mut N,
+) -> Result<String, NotifyError> {
+    let message = format!("Breaking news! {}", a.summarize());
+    notifier.send(&message)?;
+    Ok(message)
 }
 
-pub fn notify_bound_mulit_mix_where<T, U>(a: T, _b: U) -> String
+pub fn notify_bound_mulit_mix_where<T, U, N>(
+    a: T,
+    _b: U,
+    notifier: &mut N,
+) -> Result<String, NotifyError>
 where
     T: Summary + std::fmt::Display,
     U: Clone + core::fmt::Debug,
+    N: Notifier + ?Sized,
 {
-    format!("Breaking news! {}", a.summarize())
+    let message = format!("Breaking news! {}", a.summarize());
+    notifier.send(&message)?;
+    Ok(message)
 }
 
 pub fn notify_return_trait(s: &str) -> impl Summary {
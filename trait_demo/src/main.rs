@@ -1,5 +1,13 @@
+use std::env;
+use std::process;
+use trait_demo::feed::{Feed, Timeline};
+use trait_demo::ingest;
+use trait_demo::notify::StdoutNotifier;
+use trait_demo::NewArticle;
 use trait_demo::Summary;
+use trait_demo::SummaryOptions;
 use trait_demo::Tweet;
+use trait_demo::{notify, notify_return_trait};
 
 fn largest<T: PartialOrd + Clone>(list: &[T]) -> &T {
     let mut largest = &list[0];
@@ -21,7 +29,33 @@ fn check() {
     println!("The largest number is {}", result);
 }
 
+// 读取命令行给出的订阅源文件，逐条输出摘要
+fn summarize_feeds(paths: &[String]) {
+    let options = SummaryOptions::new()
+        .strip_html(true)
+        .max_chars(80)
+        .word_boundary(true);
+    for path in paths {
+        let feed = ingest::load(path).unwrap_or_else(|err| {
+            eprintln!("Problem reading feed {}: {}", path, err);
+            process::exit(1);
+        });
+        for warning in &feed.warnings {
+            eprintln!("{}: {}", path, warning);
+        }
+        println!("{} ({} articles)", feed.title, feed.articles.len());
+        for article in &feed.articles {
+            println!("  {}", article.summarize_with(&options));
+        }
+    }
+}
+
 fn main() {
+    let paths: Vec<String> = env::args().skip(1).collect();
+    if !paths.is_empty() {
+        summarize_feeds(&paths);
+        return;
+    }
     check();
     let tweet = Tweet {
         username: String::from("horse_ebooks"),
@@ -30,5 +64,27 @@ fn main() {
         retweet: false,
     };
 
-    println!("one new tweet: {}", tweet.summarize())
+    println!("one new tweet: {}", tweet.summarize());
+
+    // 把推文和新闻合并成一份摘要
+    let mut tweets = Feed::new("tweets");
+    tweets.push(1_700_000_000, tweet);
+    let mut news = Feed::new("news");
+    news.push(
+        1_700_003_600,
+        NewArticle {
+            headline: String::from("Penguins win the Stanley Cup Championship!"),
+            location: String::from("Pittsburgh, PA, USA"),
+            author: String::from("Iceburgh"),
+            content: String::from(
+                "The Pittsburgh Penguins once again are the best hockey team in the NHL.",
+            ),
+        },
+    );
+    print!("{}", Timeline::merge(&[&tweets, &news]).digest("Digest"));
+
+    let mut stdout = StdoutNotifier;
+    if let Err(err) = notify(notify_return_trait("demo"), &mut stdout) {
+        eprintln!("Problem sending notification: {}", err);
+    }
 }
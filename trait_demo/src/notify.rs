@@ -0,0 +1,726 @@
+// 通知渠道：标准输出、追加到文件、本地 HTTP webhook 和内存（测试用），
+// 以及可以叠加在任意渠道上的批量发送、限流和失败重试
+use crate::json;
+use std::collections::VecDeque;
+use std::fmt;
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::path::Path;
+use std::thread;
+use std::time::{Duration, Instant};
+
+#[derive(Debug)]
+pub enum NotifyError {
+    Io(io::Error),
+    // 服务器返回的非 2xx 状态码
+    Http(u16),
+    InvalidUrl(String),
+}
+
+impl fmt::Display for NotifyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            NotifyError::Io(e) => write!(f, "io error: {}", e),
+            NotifyError::Http(status) => write!(f, "webhook returned HTTP {}", status),
+            NotifyError::InvalidUrl(url) => write!(f, "invalid webhook url `{}`", url),
+        }
+    }
+}
+
+impl NotifyError {
+    // 连接或读写失败、服务器 5xx 可能是暂时的，其他错误重试也没用
+    pub fn is_transient(&self) -> bool {
+        matches!(self, NotifyError::Io(_) | NotifyError::Http(500..=599))
+    }
+}
+
+impl std::error::Error for NotifyError {}
+
+impl From<io::Error> for NotifyError {
+    fn from(e: io::Error) -> Self {
+        NotifyError::Io(e)
+    }
+}
+
+pub trait Notifier {
+    fn send(&mut self, message: &str) -> Result<(), NotifyError>;
+
+    // 一次发送多条，默认逐条发送
+    fn send_batch(&mut self, messages: &[String]) -> Result<(), NotifyError> {
+        for message in messages {
+            self.send(message)?;
+        }
+        Ok(())
+    }
+
+    // 发出缓存中的消息
+    fn flush(&mut self) -> Result<(), NotifyError> {
+        Ok(())
+    }
+
+    // 下一次调用是对上次失败的重试，由 Retry 调用；
+    // 记得哪些部分失败了的渠道（如 Broadcast）可以只重发失败的部分
+    fn retry_failed(&mut self) {}
+}
+
+pub struct StdoutNotifier;
+
+impl Notifier for StdoutNotifier {
+    fn send(&mut self, message: &str) -> Result<(), NotifyError> {
+        let mut out = io::stdout().lock();
+        writeln!(out, "{}", message)?;
+        Ok(())
+    }
+}
+
+// 每条消息追加一行
+pub struct FileNotifier {
+    file: File,
+}
+
+impl FileNotifier {
+    pub fn open(path: impl AsRef<Path>) -> Result<FileNotifier, NotifyError> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(FileNotifier { file })
+    }
+}
+
+impl Notifier for FileNotifier {
+    fn send(&mut self, message: &str) -> Result<(), NotifyError> {
+        writeln!(self.file, "{}", message)?;
+        Ok(())
+    }
+
+    // 一批消息合成一次写入
+    fn send_batch(&mut self, messages: &[String]) -> Result<(), NotifyError> {
+        let mut text = String::new();
+        for message in messages {
+            text.push_str(message);
+            text.push('\n');
+        }
+        self.file.write_all(text.as_bytes())?;
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<(), NotifyError> {
+        self.file.flush()?;
+        Ok(())
+    }
+}
+
+// 用 HTTP/1.1 POST JSON 到本机或内网的 webhook，不支持 https
+pub struct WebhookNotifier {
+    host: String,
+    port: u16,
+    path: String,
+    timeout: Duration,
+}
+
+impl WebhookNotifier {
+    // url 形如 http://127.0.0.1:8080/hooks/news，IPv6 地址写在方括号里：http://[::1]:8080/
+    pub fn new(url: &str) -> Result<WebhookNotifier, NotifyError> {
+        let invalid = || NotifyError::InvalidUrl(String::from(url));
+        let rest = url.strip_prefix("http://").ok_or_else(invalid)?;
+        let (authority, path) = match rest.find('/') {
+            Some(i) => (&rest[..i], &rest[i..]),
+            None => (rest, "/"),
+        };
+        let (host, port) = match authority.strip_prefix('[') {
+            Some(rest) => {
+                let (host, rest) = rest.split_once(']').ok_or_else(invalid)?;
+                match rest.strip_prefix(':') {
+                    Some(port) => (host, port.parse().map_err(|_| invalid())?),
+                    None if rest.is_empty() => (host, 80),
+                    None => return Err(invalid()),
+                }
+            }
+            None => match authority.rsplit_once(':') {
+                Some((host, port)) => (host, port.parse().map_err(|_| invalid())?),
+                None => (authority, 80),
+            },
+        };
+        // 没有方括号的 IPv6 地址无法和端口区分开
+        if host.is_empty() || (host.contains(':') && !authority.starts_with('[')) {
+            return Err(invalid());
+        }
+        Ok(WebhookNotifier {
+            host: String::from(host),
+            port,
+            path: String::from(path),
+            timeout: Duration::from_secs(5),
+        })
+    }
+
+    pub fn timeout(mut self, timeout: Duration) -> WebhookNotifier {
+        self.timeout = timeout;
+        self
+    }
+
+    // 依次尝试解析出的地址，连接也受 timeout 限制
+    fn connect(&self) -> io::Result<TcpStream> {
+        let mut last = None;
+        for addr in (self.host.as_str(), self.port).to_socket_addrs()? {
+            match TcpStream::connect_timeout(&addr, self.timeout) {
+                Ok(stream) => return Ok(stream),
+                Err(e) => last = Some(e),
+            }
+        }
+        Err(last.unwrap_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("no addresses for `{}`", self.host),
+            )
+        }))
+    }
+
+    fn post(&self, body: &str) -> Result<(), NotifyError> {
+        let mut stream = self.connect()?;
+        stream.set_read_timeout(Some(self.timeout))?;
+        stream.set_write_timeout(Some(self.timeout))?;
+        let host = if self.host.contains(':') {
+            format!("[{}]", self.host)
+        } else {
+            self.host.clone()
+        };
+        let request = format!(
+            "POST {} HTTP/1.1\r\nHost: {}:{}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            self.path,
+            host,
+            self.port,
+            body.len(),
+            body
+        );
+        stream.write_all(request.as_bytes())?;
+        // 只关心状态行，例如 "HTTP/1.1 204 No Content"
+        let mut status_line = String::new();
+        BufReader::new(stream).read_line(&mut status_line)?;
+        let status = status_line
+            .split_whitespace()
+            .nth(1)
+            .and_then(|s| s.parse().ok())
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("bad status line `{}`", status_line.trim_end()),
+                )
+            })?;
+        match status {
+            200..=299 => Ok(()),
+            status => Err(NotifyError::Http(status)),
+        }
+    }
+}
+
+impl Notifier for WebhookNotifier {
+    fn send(&mut self, message: &str) -> Result<(), NotifyError> {
+        self.post(&format!("{{\"text\":{}}}", json::quote(message)))
+    }
+
+    // 一批消息放在一个请求里
+    fn send_batch(&mut self, messages: &[String]) -> Result<(), NotifyError> {
+        let texts: Vec<String> = messages.iter().map(|m| json::quote(m)).collect();
+        self.post(&format!("{{\"texts\":[{}]}}", texts.join(",")))
+    }
+}
+
+// 保存收到的消息，可以模拟失败
+#[derive(Debug, Default)]
+pub struct MemoryNotifier {
+    pub messages: Vec<String>,
+    // 每次 send_batch 调用占一项
+    pub batches: Vec<usize>,
+    failures: usize,
+}
+
+impl MemoryNotifier {
+    pub fn new() -> MemoryNotifier {
+        MemoryNotifier::default()
+    }
+
+    // 接下来的 n 次发送返回 IO 错误，模拟暂时的故障
+    pub fn fail_next(&mut self, n: usize) {
+        self.failures = n;
+    }
+
+    fn check(&mut self) -> Result<(), NotifyError> {
+        if self.failures > 0 {
+            self.failures -= 1;
+            return Err(NotifyError::Io(io::Error::other("simulated failure")));
+        }
+        Ok(())
+    }
+}
+
+impl Notifier for MemoryNotifier {
+    fn send(&mut self, message: &str) -> Result<(), NotifyError> {
+        self.check()?;
+        self.messages.push(String::from(message));
+        Ok(())
+    }
+
+    fn send_batch(&mut self, messages: &[String]) -> Result<(), NotifyError> {
+        self.check()?;
+        self.messages.extend_from_slice(messages);
+        self.batches.push(messages.len());
+        Ok(())
+    }
+}
+
+// 同时发送到多个渠道；某个渠道失败不影响其他渠道，返回第一个错误。
+// 调用 retry_failed 后（例如外面包了 Retry），下一次发送只发给上次失败的渠道，
+// 已经成功的渠道不会重复收到
+#[derive(Default)]
+pub struct Broadcast {
+    sinks: Vec<Box<dyn Notifier>>,
+    // 上次发送失败的渠道下标
+    failed: Vec<usize>,
+    retrying: bool,
+}
+
+impl Broadcast {
+    pub fn new(sinks: Vec<Box<dyn Notifier>>) -> Broadcast {
+        Broadcast {
+            sinks,
+            ..Broadcast::default()
+        }
+    }
+
+    pub fn push(&mut self, sink: Box<dyn Notifier>) {
+        self.sinks.push(sink);
+    }
+
+    pub fn sinks(&self) -> &[Box<dyn Notifier>] {
+        &self.sinks
+    }
+
+    fn each(
+        &mut self,
+        mut f: impl FnMut(&mut dyn Notifier) -> Result<(), NotifyError>,
+    ) -> Result<(), NotifyError> {
+        let failed = std::mem::take(&mut self.failed);
+        let targets: Vec<usize> = if std::mem::take(&mut self.retrying) {
+            failed
+                .into_iter()
+                .filter(|i| *i < self.sinks.len())
+                .collect()
+        } else {
+            (0..self.sinks.len()).collect()
+        };
+        let mut first = None;
+        for i in targets {
+            if let Err(e) = f(self.sinks[i].as_mut()) {
+                self.failed.push(i);
+                first.get_or_insert(e);
+            }
+        }
+        first.map_or(Ok(()), Err)
+    }
+}
+
+impl Notifier for Broadcast {
+    fn send(&mut self, message: &str) -> Result<(), NotifyError> {
+        self.each(|sink| sink.send(message))
+    }
+
+    fn send_batch(&mut self, messages: &[String]) -> Result<(), NotifyError> {
+        self.each(|sink| sink.send_batch(messages))
+    }
+
+    fn flush(&mut self) -> Result<(), NotifyError> {
+        self.each(|sink| sink.flush())
+    }
+
+    fn retry_failed(&mut self) {
+        self.retrying = true;
+        for i in &self.failed {
+            if let Some(sink) = self.sinks.get_mut(*i) {
+                sink.retry_failed();
+            }
+        }
+    }
+}
+
+// 攒够 size 条再一起发送；丢弃前应当调用 flush 并处理它的错误
+pub struct Batching<N: Notifier> {
+    inner: N,
+    size: usize,
+    pending: Vec<String>,
+}
+
+impl<N: Notifier> Batching<N> {
+    pub fn new(inner: N, size: usize) -> Batching<N> {
+        Batching {
+            inner,
+            size: size.max(1),
+            pending: Vec::new(),
+        }
+    }
+
+    pub fn inner(&self) -> &N {
+        &self.inner
+    }
+
+    // 发送失败时消息仍然留在缓存里，下次再试
+    fn send_pending(&mut self) -> Result<(), NotifyError> {
+        if self.pending.is_empty() {
+            return Ok(());
+        }
+        self.inner.send_batch(&self.pending)?;
+        self.pending.clear();
+        Ok(())
+    }
+}
+
+impl<N: Notifier> Notifier for Batching<N> {
+    fn send(&mut self, message: &str) -> Result<(), NotifyError> {
+        self.pending.push(String::from(message));
+        if self.pending.len() >= self.size {
+            self.send_pending()?;
+        }
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<(), NotifyError> {
+        self.send_pending()?;
+        self.inner.flush()
+    }
+
+    fn retry_failed(&mut self) {
+        self.inner.retry_failed();
+    }
+}
+
+// 没有 flush 就丢弃时尽量补发，失败只能记录下来；
+// 发送出错后提前返回也会走到这里，所以不能 panic
+impl<N: Notifier> Drop for Batching<N> {
+    fn drop(&mut self) {
+        if self.pending.is_empty() {
+            return;
+        }
+        let count = self.pending.len();
+        if let Err(e) = self.send_pending() {
+            eprintln!("Problem sending {} batched notifications: {}", count, e);
+        }
+    }
+}
+
+// 滑动窗口限流：任意 per 时间内最多发送 max 次，超出时等待
+pub struct RateLimited<N: Notifier> {
+    inner: N,
+    max: usize,
+    per: Duration,
+    sent: VecDeque<Instant>,
+}
+
+impl<N: Notifier> RateLimited<N> {
+    pub fn new(inner: N, max: usize, per: Duration) -> RateLimited<N> {
+        RateLimited {
+            inner,
+            max: max.max(1),
+            per,
+            sent: VecDeque::new(),
+        }
+    }
+
+    pub fn inner(&self) -> &N {
+        &self.inner
+    }
+
+    fn wait(&mut self) {
+        let now = Instant::now();
+        while self
+            .sent
+            .front()
+            .is_some_and(|t| now.duration_since(*t) >= self.per)
+        {
+            self.sent.pop_front();
+        }
+        if self.sent.len() >= self.max {
+            let oldest = self.sent.pop_front().unwrap();
+            thread::sleep(self.per.saturating_sub(now.duration_since(oldest)));
+        }
+        self.sent.push_back(Instant::now());
+    }
+}
+
+impl<N: Notifier> Notifier for RateLimited<N> {
+    fn send(&mut self, message: &str) -> Result<(), NotifyError> {
+        self.wait();
+        self.inner.send(message)
+    }
+
+    // 一批算一次
+    fn send_batch(&mut self, messages: &[String]) -> Result<(), NotifyError> {
+        self.wait();
+        self.inner.send_batch(messages)
+    }
+
+    fn flush(&mut self) -> Result<(), NotifyError> {
+        self.inner.flush()
+    }
+
+    fn retry_failed(&mut self) {
+        self.inner.retry_failed();
+    }
+}
+
+// 失败后重试，每次等待的时间翻倍；只重试 IO 错误和 5xx，见 NotifyError::is_transient。
+// 包装 Broadcast 时只会重发给失败的渠道
+pub struct Retry<N: Notifier> {
+    inner: N,
+    attempts: usize,
+    backoff: Duration,
+}
+
+impl<N: Notifier> Retry<N> {
+    // attempts 为总尝试次数，backoff 为第一次重试前的等待时间
+    pub fn new(inner: N, attempts: usize, backoff: Duration) -> Retry<N> {
+        Retry {
+            inner,
+            attempts: attempts.max(1),
+            backoff,
+        }
+    }
+
+    pub fn inner(&self) -> &N {
+        &self.inner
+    }
+
+    fn run(
+        &mut self,
+        mut f: impl FnMut(&mut N) -> Result<(), NotifyError>,
+    ) -> Result<(), NotifyError> {
+        let mut delay = self.backoff;
+        let mut attempt = 1;
+        loop {
+            match f(&mut self.inner) {
+                Err(e) if !e.is_transient() || attempt >= self.attempts => return Err(e),
+                Err(_) => {
+                    self.inner.retry_failed();
+                    thread::sleep(delay);
+                    delay = delay.saturating_mul(2);
+                    attempt += 1;
+                }
+                Ok(()) => return Ok(()),
+            }
+        }
+    }
+}
+
+impl<N: Notifier> Notifier for Retry<N> {
+    fn send(&mut self, message: &str) -> Result<(), NotifyError> {
+        self.run(|inner| inner.send(message))
+    }
+
+    fn send_batch(&mut self, messages: &[String]) -> Result<(), NotifyError> {
+        self.run(|inner| inner.send_batch(messages))
+    }
+
+    fn flush(&mut self) -> Result<(), NotifyError> {
+        self.run(|inner| inner.flush())
+    }
+
+    fn retry_failed(&mut self) {
+        self.inner.retry_failed();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::fs;
+    use std::io::Read;
+    use std::net::TcpListener;
+    use std::rc::Rc;
+
+    #[test]
+    fn notify_functions_use_the_notifier() {
+        let tweet = crate::Tweet {
+            username: String::from("rustlang"),
+            content: String::from("1.0 released"),
+            reply: false,
+            retweet: false,
+        };
+        let mut memory = MemoryNotifier::new();
+        crate::notify(tweet, &mut memory).unwrap();
+        assert_eq!(
+            vec!["Breaking news! rustlang: 1.0 released"],
+            memory.messages
+        );
+        let tweet = crate::Tweet {
+            username: String::from("rustlang"),
+            content: String::from("2.0 released"),
+            reply: false,
+            retweet: false,
+        };
+        let sink: &mut dyn Notifier = &mut memory;
+        crate::notify_bound(tweet, sink).unwrap();
+        assert_eq!(2, memory.messages.len());
+    }
+
+    #[test]
+    fn batches_and_retries() {
+        let mut batching = Batching::new(MemoryNotifier::new(), 2);
+        batching.send("a").unwrap();
+        assert!(batching.inner().messages.is_empty());
+        batching.send("b").unwrap();
+        batching.send("c").unwrap();
+        batching.flush().unwrap();
+        assert_eq!(vec!["a", "b", "c"], batching.inner().messages);
+        assert_eq!(vec![2, 1], batching.inner().batches);
+
+        let mut memory = MemoryNotifier::new();
+        memory.fail_next(2);
+        let mut retry = Retry::new(memory, 3, Duration::from_millis(1));
+        retry.send("x").unwrap();
+        assert_eq!(vec!["x"], retry.inner().messages);
+
+        let mut memory = MemoryNotifier::new();
+        memory.fail_next(3);
+        let mut retry = Retry::new(memory, 2, Duration::from_millis(1));
+        assert_eq!(
+            "io error: simulated failure",
+            retry.send("y").unwrap_err().to_string()
+        );
+    }
+
+    #[test]
+    fn rate_limit_waits_for_window() {
+        let mut limited = RateLimited::new(MemoryNotifier::new(), 2, Duration::from_millis(50));
+        let start = Instant::now();
+        for message in ["1", "2", "3"] {
+            limited.send(message).unwrap();
+        }
+        assert!(start.elapsed() >= Duration::from_millis(50));
+        assert_eq!(3, limited.inner().messages.len());
+    }
+
+    #[test]
+    fn appends_to_file_and_broadcasts() {
+        let path =
+            std::env::temp_dir().join(format!("trait_demo_notify_{}.log", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let mut broadcast = Broadcast::new(vec![
+            Box::new(FileNotifier::open(&path).unwrap()),
+            Box::new(FileNotifier::open(&path).unwrap()),
+        ]);
+        broadcast.send("one").unwrap();
+        broadcast.flush().unwrap();
+        assert_eq!("one\none\n", fs::read_to_string(&path).unwrap());
+        fs::remove_file(&path).unwrap();
+    }
+
+    // 测试结束后还能查看收到的消息
+    struct Shared(Rc<RefCell<MemoryNotifier>>);
+
+    impl Notifier for Shared {
+        fn send(&mut self, message: &str) -> Result<(), NotifyError> {
+            self.0.borrow_mut().send(message)
+        }
+    }
+
+    #[test]
+    fn retried_broadcast_skips_sinks_that_succeeded() {
+        let ok = Rc::new(RefCell::new(MemoryNotifier::new()));
+        let flaky = Rc::new(RefCell::new(MemoryNotifier::new()));
+        flaky.borrow_mut().fail_next(1);
+        let broadcast = Broadcast::new(vec![
+            Box::new(Shared(Rc::clone(&ok))),
+            Box::new(Shared(Rc::clone(&flaky))),
+        ]);
+        let mut retry = Retry::new(broadcast, 3, Duration::from_millis(1));
+        retry.send("one").unwrap();
+        retry.send("two").unwrap();
+        assert_eq!(vec!["one", "two"], ok.borrow().messages);
+        assert_eq!(vec!["one", "two"], flaky.borrow().messages);
+
+        // 不经过重试再发同样的文字是新的通知，所有渠道都会收到
+        flaky.borrow_mut().fail_next(1);
+        let mut broadcast = Broadcast::new(vec![Box::new(Shared(Rc::clone(&ok)))]);
+        broadcast.push(Box::new(Shared(Rc::clone(&flaky))));
+        assert!(broadcast.send("three").is_err());
+        broadcast.send("three").unwrap();
+        assert_eq!(vec!["one", "two", "three", "three"], ok.borrow().messages);
+        assert_eq!(vec!["one", "two", "three"], flaky.borrow().messages);
+        assert_eq!(2, broadcast.sinks().len());
+    }
+
+    // 读完请求头和 Content-Length 指定长度的正文
+    fn read_request(stream: &mut TcpStream) -> String {
+        let mut reader = BufReader::new(stream);
+        let mut request = String::new();
+        let mut length = 0;
+        loop {
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap();
+            if let Some(value) = line.strip_prefix("Content-Length: ") {
+                length = value.trim().parse().unwrap();
+            }
+            request.push_str(&line);
+            if line == "\r\n" {
+                break;
+            }
+        }
+        let mut body = vec![0; length];
+        reader.read_exact(&mut body).unwrap();
+        request + &String::from_utf8(body).unwrap()
+    }
+
+    #[test]
+    fn posts_json_to_webhook() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = thread::spawn(move || {
+            let mut requests = Vec::new();
+            for status in [
+                "204 No Content",
+                "500 Internal Server Error",
+                "404 Not Found",
+            ] {
+                let (mut stream, _) = listener.accept().unwrap();
+                requests.push(read_request(&mut stream));
+                write!(stream, "HTTP/1.1 {}\r\nContent-Length: 0\r\n\r\n", status).unwrap();
+            }
+            requests
+        });
+        let url = format!("http://127.0.0.1:{}/hooks/news", port);
+        let mut webhook = WebhookNotifier::new(&url).unwrap();
+        webhook.send("say \"hi\"").unwrap();
+        assert_eq!(
+            "webhook returned HTTP 500",
+            webhook.send("again").unwrap_err().to_string()
+        );
+        // 4xx 不会重试
+        let mut retry = Retry::new(
+            WebhookNotifier::new(&url).unwrap(),
+            3,
+            Duration::from_millis(1),
+        );
+        assert!(matches!(retry.send("x"), Err(NotifyError::Http(404))));
+        let requests = server.join().unwrap();
+        assert_eq!(3, requests.len());
+        assert!(requests[0].starts_with("POST /hooks/news HTTP/1.1\r\n"));
+        assert!(requests[0].ends_with("\r\n\r\n{\"text\":\"say \\\"hi\\\"\"}"));
+
+        assert!(matches!(
+            WebhookNotifier::new("https://example.com"),
+            Err(NotifyError::InvalidUrl(_))
+        ));
+    }
+
+    #[test]
+    fn parses_bracketed_ipv6_hosts() {
+        let webhook = WebhookNotifier::new("http://[::1]:8080/hook").unwrap();
+        assert_eq!(
+            ("::1", 8080, "/hook"),
+            (webhook.host.as_str(), webhook.port, webhook.path.as_str())
+        );
+        let webhook = WebhookNotifier::new("http://[::1]").unwrap();
+        assert_eq!(("::1", 80), (webhook.host.as_str(), webhook.port));
+        for url in ["http://::1:8080/", "http://[::1/", "http://[::1]x/"] {
+            assert!(WebhookNotifier::new(url).is_err(), "{}", url);
+        }
+    }
+}
@@ -0,0 +1,356 @@
+// 摘要选项：去掉 HTML、只取第一句、按字符或字素限制长度
+// 截断总是落在字符（或字素）边界上，不会切开多字节字符
+use crate::marks;
+use crate::xml::unescape;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LengthUnit {
+    // Unicode 标量值
+    #[default]
+    Chars,
+    // 用户看到的字符，如带组合符号的字母、多个码点组成的 emoji
+    Graphemes,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SummaryOptions {
+    max_len: Option<usize>,
+    unit: LengthUnit,
+    word_boundary: bool,
+    ellipsis: String,
+    first_sentence: bool,
+    strip_html: bool,
+}
+
+impl Default for SummaryOptions {
+    fn default() -> Self {
+        SummaryOptions::new()
+    }
+}
+
+impl SummaryOptions {
+    // 默认不做任何处理
+    pub fn new() -> SummaryOptions {
+        SummaryOptions {
+            max_len: None,
+            unit: LengthUnit::Chars,
+            word_boundary: false,
+            ellipsis: String::from("…"),
+            first_sentence: false,
+            strip_html: false,
+        }
+    }
+
+    // 最多 n 个字符，包括省略号
+    pub fn max_chars(mut self, n: usize) -> SummaryOptions {
+        self.max_len = Some(n);
+        self.unit = LengthUnit::Chars;
+        self
+    }
+
+    pub fn max_graphemes(mut self, n: usize) -> SummaryOptions {
+        self.max_len = Some(n);
+        self.unit = LengthUnit::Graphemes;
+        self
+    }
+
+    // 截断时退回到最后一个完整的单词
+    pub fn word_boundary(mut self, yes: bool) -> SummaryOptions {
+        self.word_boundary = yes;
+        self
+    }
+
+    pub fn ellipsis(mut self, ellipsis: &str) -> SummaryOptions {
+        self.ellipsis = String::from(ellipsis);
+        self
+    }
+
+    pub fn first_sentence(mut self, yes: bool) -> SummaryOptions {
+        self.first_sentence = yes;
+        self
+    }
+
+    pub fn strip_html(mut self, yes: bool) -> SummaryOptions {
+        self.strip_html = yes;
+        self
+    }
+
+    // 依次去掉 HTML、取第一句、截断
+    pub fn apply(&self, text: &str) -> String {
+        self.apply_prefixed("", text)
+    }
+
+    // 只处理 text，prefix 原样保留，但长度限制作用于拼接后的整个结果
+    pub fn apply_prefixed(&self, prefix: &str, text: &str) -> String {
+        let mut text = if self.strip_html {
+            strip_html(text)
+        } else {
+            String::from(text)
+        };
+        if self.first_sentence {
+            text = String::from(first_sentence(&text));
+        }
+        let text = format!("{}{}", prefix, text);
+        match self.max_len {
+            Some(max) => self.truncate(&text, max),
+            None => text,
+        }
+    }
+
+    fn split<'a>(&self, text: &'a str) -> Vec<&'a str> {
+        match self.unit {
+            LengthUnit::Chars => text
+                .char_indices()
+                .map(|(i, c)| &text[i..i + c.len_utf8()])
+                .collect(),
+            LengthUnit::Graphemes => graphemes(text),
+        }
+    }
+
+    fn truncate(&self, text: &str, max: usize) -> String {
+        let units = self.split(text);
+        if units.len() <= max {
+            return String::from(text);
+        }
+        let ellipsis_len = self.split(&self.ellipsis).len();
+        // 放不下省略号时直接截断
+        if max <= ellipsis_len {
+            return units[..max].concat();
+        }
+        let mut kept = &units[..max - ellipsis_len];
+        if self.word_boundary {
+            // 被截断的位置正好在单词之间时不需要回退
+            let cut_in_word = !units[kept.len()].trim().is_empty();
+            if cut_in_word {
+                if let Some(space) = kept.iter().rposition(|u| u.trim().is_empty()) {
+                    kept = &kept[..space];
+                }
+            }
+        }
+        let mut out = kept.concat();
+        out.truncate(out.trim_end().len());
+        out.push_str(&self.ellipsis);
+        out
+    }
+}
+
+// 第一句话，包含句末标点；找不到句末时返回全部文字
+pub fn first_sentence(text: &str) -> &str {
+    let text = text.trim();
+    let mut chars = text.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        let end = i + c.len_utf8();
+        match c {
+            // 中日文标点后面不需要空格
+            '。' | '！' | '？' => return &text[..end],
+            '.' | '!' | '?' if chars.peek().is_none_or(|(_, next)| next.is_whitespace()) => {
+                return &text[..end];
+            }
+            _ => {}
+        }
+    }
+    text
+}
+
+const BLOCK_TAGS: &[&str] = &[
+    "address",
+    "article",
+    "blockquote",
+    "br",
+    "dd",
+    "div",
+    "dl",
+    "dt",
+    "figcaption",
+    "footer",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "header",
+    "hr",
+    "li",
+    "ol",
+    "p",
+    "pre",
+    "section",
+    "table",
+    "td",
+    "th",
+    "tr",
+    "ul",
+];
+
+// 去掉标签和注释，解码实体，合并空白；script 和 style 的内容整个丢掉
+pub fn strip_html(html: &str) -> String {
+    let mut text = String::with_capacity(html.len());
+    let mut rest = html;
+    while let Some(i) = rest.find('<') {
+        text.push_str(&rest[..i]);
+        rest = &rest[i..];
+        if let Some(comment) = rest.strip_prefix("<!--") {
+            rest = comment.find("-->").map_or("", |end| &comment[end + 3..]);
+            continue;
+        }
+        let end = match rest.find('>') {
+            Some(end) => end,
+            // 没有结束的 '<' 当作普通文字
+            None => {
+                text.push_str(rest);
+                rest = "";
+                break;
+            }
+        };
+        let tag = rest[1..end].trim_start_matches('/').to_ascii_lowercase();
+        let name: String = tag
+            .chars()
+            .take_while(|c| c.is_ascii_alphanumeric())
+            .collect();
+        rest = &rest[end + 1..];
+        if (name == "script" || name == "style") && !tag.ends_with('/') {
+            let close = format!("</{}", name);
+            rest = match rest.to_ascii_lowercase().find(&close) {
+                Some(i) => rest[i..].find('>').map_or("", |e| &rest[i + e + 1..]),
+                None => "",
+            };
+        }
+        // 块级元素之间留一个空格，避免单词粘在一起
+        if BLOCK_TAGS.contains(&name.as_str()) {
+            text.push(' ');
+        }
+    }
+    text.push_str(rest);
+    let text = unescape(&text);
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+// 所有组合符号（Mn、Mc、Me）都并入前一个字素；不处理韩文音节、
+// 印度系文字的辅音连写等更细的规则
+fn is_extend(c: char) -> bool {
+    marks::is_mark(c)
+        || matches!(c as u32,
+            0x1F3FB..=0x1F3FF    // 肤色
+            | 0xE0020..=0xE007F  // 标签字符
+            | 0x200C)
+}
+
+fn is_regional_indicator(c: char) -> bool {
+    matches!(c as u32, 0x1F1E6..=0x1F1FF)
+}
+
+// 近似的字素切分：合并组合符号、变体选择符、零宽连接的 emoji 序列、
+// 国旗（两个区域指示符）和 CRLF，足够用来安全地截断
+pub fn graphemes(text: &str) -> Vec<&str> {
+    let mut out = Vec::new();
+    let mut start = 0;
+    let mut prev: Option<char> = None;
+    // 当前字素中区域指示符的个数
+    let mut regional = 0;
+    for (i, c) in text.char_indices() {
+        let joins = match prev {
+            None => false,
+            Some(p) => {
+                (p == '\r' && c == '\n')
+                    || is_extend(c)
+                    || c == '\u{200D}'
+                    || p == '\u{200D}'
+                    || (is_regional_indicator(c) && regional % 2 == 1)
+            }
+        };
+        if !joins && i > 0 {
+            out.push(&text[start..i]);
+            start = i;
+            regional = 0;
+        }
+        if is_regional_indicator(c) {
+            regional += 1;
+        }
+        prev = Some(c);
+    }
+    if start < text.len() {
+        out.push(&text[start..]);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn truncates_on_character_boundaries() {
+        let options = SummaryOptions::new().max_chars(5);
+        assert_eq!("汉字很长…", options.apply("汉字很长的句子"));
+        assert_eq!("short", options.apply("short"));
+        let words = SummaryOptions::new().max_chars(12).word_boundary(true);
+        assert_eq!("hello big…", words.apply("hello big world"));
+        assert_eq!("hello big…", words.apply("hello big  world"));
+        // 一个单词比限制还长时只能在单词中间截断
+        assert_eq!("abcdefghijk…", words.apply("abcdefghijklmnop"));
+        let dots = SummaryOptions::new().max_chars(2).ellipsis("...");
+        assert_eq!("ab", dots.apply("abcdef"));
+    }
+
+    #[test]
+    fn counts_graphemes() {
+        let family = "👨\u{200D}👩\u{200D}👧";
+        let text = format!("e\u{301}{}🇨🇳🇯🇵\r\nx", family);
+        let parts = graphemes(&text);
+        assert_eq!(vec!["e\u{301}", family, "🇨🇳", "🇯🇵", "\r\n", "x"], parts);
+        let options = SummaryOptions::new().max_graphemes(3);
+        assert_eq!(format!("e\u{301}{}…", family), options.apply(&text));
+    }
+
+    #[test]
+    fn keeps_marks_of_other_scripts() {
+        // 阿拉伯文的元音符号和希伯来文的点都属于前一个字母
+        let arabic = "مَرْحَبًا";
+        assert_eq!(vec!["مَ", "رْ", "حَ", "بً", "ا"], graphemes(arabic));
+        let options = SummaryOptions::new().max_graphemes(3);
+        assert_eq!("مَرْ…", options.apply(arabic));
+        assert_eq!(vec!["שָׁ", "ל", "וֹ", "ם"], graphemes("שָׁלוֹם"));
+        assert_eq!(vec!["कि", "ता", "ब"], graphemes("किताब"));
+    }
+
+    #[test]
+    fn limit_covers_the_whole_summary() {
+        use crate::{NewArticle, Summary, Tweet};
+
+        let article = NewArticle {
+            headline: String::from("Big news. Read on"),
+            location: String::new(),
+            author: String::from("a"),
+            content: String::from("<p>First sentence. Second one.</p>"),
+        };
+        let clean = SummaryOptions::new().strip_html(true).first_sentence(true);
+        // 取第一句只作用于正文，标题中的句号不算
+        assert_eq!(
+            "Big news. Read on: First sentence.",
+            article.summarize_with(&clean)
+        );
+        let short = clean.max_chars(24);
+        assert_eq!("Big news. Read on: Firs…", article.summarize_with(&short));
+        assert_eq!(24, article.summarize_with(&short).chars().count());
+
+        let tweet = Tweet {
+            username: String::from("horse_ebooks"),
+            content: String::from("of course, as you probably already know, people"),
+            reply: false,
+            retweet: false,
+        };
+        let options = SummaryOptions::new().max_chars(20);
+        assert_eq!("horse_ebooks: of co…", tweet.summarize_with(&options));
+    }
+
+    #[test]
+    fn strips_html_and_extracts_first_sentence() {
+        let html = "<p>Rust 1.0 is <b>out</b>!&nbsp;Read&amp;enjoy.</p><script>alert('x')</script><p>Next</p>";
+        assert_eq!("Rust 1.0 is out! Read&enjoy. Next", strip_html(html));
+        let options = SummaryOptions::new().strip_html(true).first_sentence(true);
+        assert_eq!("Rust 1.0 is out!", options.apply(html));
+        assert_eq!("第一句。", first_sentence("第一句。第二句。"));
+        assert_eq!("no end", first_sentence("  no end  "));
+    }
+}
@@ -0,0 +1,364 @@
+// 只够读取订阅源的简单 XML 解析器：支持属性、注释、CDATA 和常见实体，
+// 不处理 DTD 和命名空间。遇到不配对的标签时尽量继续，而不是报错
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Node {
+    Element(Element),
+    Text(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Element {
+    // 保留前缀，如 "dc:creator"
+    pub name: String,
+    pub attrs: Vec<(String, String)>,
+    pub children: Vec<Node>,
+}
+
+impl Element {
+    pub fn attr(&self, name: &str) -> Option<&str> {
+        self.attrs
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| v.as_str())
+    }
+
+    pub fn elements(&self) -> impl Iterator<Item = &Element> {
+        self.children.iter().filter_map(|c| match c {
+            Node::Element(e) => Some(e),
+            Node::Text(_) => None,
+        })
+    }
+
+    pub fn children_named<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Element> {
+        self.elements().filter(move |e| e.name == name)
+    }
+
+    pub fn child(&self, name: &str) -> Option<&Element> {
+        self.elements().find(|e| e.name == name)
+    }
+
+    // 所有后代文字连起来，去掉首尾空白；用显式的栈，嵌套再深也不会爆栈
+    pub fn text(&self) -> String {
+        let mut out = String::new();
+        let mut stack = vec![self.children.iter()];
+        while let Some(children) = stack.last_mut() {
+            match children.next() {
+                Some(Node::Text(text)) => out.push_str(text),
+                Some(Node::Element(e)) => stack.push(e.children.iter()),
+                None => {
+                    stack.pop();
+                }
+            }
+        }
+        String::from(out.trim())
+    }
+
+    // 子元素的文字，没有或为空时返回 None
+    pub fn child_text(&self, name: &str) -> Option<String> {
+        self.child(name).map(|e| e.text()).filter(|t| !t.is_empty())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct XmlError {
+    // 字节偏移
+    pub offset: usize,
+    pub message: String,
+}
+
+impl fmt::Display for XmlError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "at byte {}: {}", self.offset, self.message)
+    }
+}
+
+impl std::error::Error for XmlError {}
+
+// 把 &amp; 等实体换成字符，不认识的实体原样保留
+pub fn unescape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(i) = rest.find('&') {
+        out.push_str(&rest[..i]);
+        rest = &rest[i..];
+        let end = match rest.find(';') {
+            Some(end) if end <= 10 => end,
+            _ => {
+                out.push('&');
+                rest = &rest[1..];
+                continue;
+            }
+        };
+        let entity = &rest[1..end];
+        let c = match entity {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            "nbsp" => Some('\u{a0}'),
+            _ => {
+                let code =
+                    if let Some(hex) = entity.strip_prefix("#x").or(entity.strip_prefix("#X")) {
+                        u32::from_str_radix(hex, 16).ok()
+                    } else {
+                        entity.strip_prefix('#').and_then(|d| d.parse().ok())
+                    };
+                code.and_then(char::from_u32)
+            }
+        };
+        match c {
+            Some(c) => {
+                out.push(c);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+// 元素最多嵌套的层数，避免恶意输入把栈耗尽
+const MAX_DEPTH: usize = 256;
+
+struct Parser<'a> {
+    text: &'a str,
+    pos: usize,
+    // 当前标签、注释等开始的位置，缺少结尾时报告在这里
+    start: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn rest(&self) -> &'a str {
+        &self.text[self.pos..]
+    }
+
+    fn error(&self, message: &str) -> XmlError {
+        XmlError {
+            offset: self.pos,
+            message: String::from(message),
+        }
+    }
+
+    // 跳到 end 之后，返回中间的内容
+    fn until(&mut self, end: &str) -> Result<&'a str, XmlError> {
+        match self.rest().find(end) {
+            Some(i) => {
+                let content = &self.rest()[..i];
+                self.pos += i + end.len();
+                Ok(content)
+            }
+            None => Err(XmlError {
+                offset: self.start,
+                message: format!("missing `{}`", end),
+            }),
+        }
+    }
+
+    fn name(&mut self) -> &'a str {
+        let rest = self.rest();
+        let len = rest
+            .find(|c: char| c.is_whitespace() || "/>=\"'<".contains(c))
+            .unwrap_or(rest.len());
+        self.pos += len;
+        &rest[..len]
+    }
+
+    fn skip_space(&mut self) {
+        let rest = self.rest();
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+
+    // 已经读过 '<'；返回元素和是否自闭合
+    fn start_tag(&mut self) -> Result<(Element, bool), XmlError> {
+        let name = self.name();
+        if name.is_empty() {
+            return Err(self.error("expected a tag name"));
+        }
+        let mut element = Element {
+            name: String::from(name),
+            ..Element::default()
+        };
+        loop {
+            self.skip_space();
+            let rest = self.rest();
+            if rest.starts_with("/>") {
+                self.pos += 2;
+                return Ok((element, true));
+            }
+            if rest.starts_with('>') {
+                self.pos += 1;
+                return Ok((element, false));
+            }
+            if rest.is_empty() || rest.starts_with('<') {
+                return Err(self.error(&format!("unterminated tag `<{}`", element.name)));
+            }
+            let name = self.name();
+            if name.is_empty() {
+                // 跳过无法识别的字符
+                self.pos += rest.chars().next().map_or(1, char::len_utf8);
+                continue;
+            }
+            self.skip_space();
+            // 没有值的属性按空字符串处理
+            let mut value = String::new();
+            if self.rest().starts_with('=') {
+                self.pos += 1;
+                self.skip_space();
+                let quote = self.rest().chars().next();
+                value = match quote {
+                    Some(q @ ('"' | '\'')) => {
+                        self.pos += 1;
+                        unescape(self.until(if q == '"' { "\"" } else { "'" })?)
+                    }
+                    _ => String::from(self.name()),
+                };
+            }
+            element.attrs.push((String::from(name), value));
+        }
+    }
+}
+
+// 解析整个文档，返回根元素
+pub fn parse(text: &str) -> Result<Element, XmlError> {
+    let mut parser = Parser {
+        text,
+        pos: 0,
+        start: 0,
+    };
+    // 栈底是虚拟的文档节点
+    let mut stack = vec![Element::default()];
+    while !parser.rest().is_empty() {
+        parser.start = parser.pos;
+        let rest = parser.rest();
+        if !rest.starts_with('<') {
+            let len = rest.find('<').unwrap_or(rest.len());
+            let text = unescape(&rest[..len]);
+            parser.pos += len;
+            if stack.len() > 1 {
+                stack.last_mut().unwrap().children.push(Node::Text(text));
+            }
+            continue;
+        }
+        if rest.starts_with("<!--") {
+            parser.pos += 4;
+            parser.until("-->")?;
+        } else if rest.starts_with("<![CDATA[") {
+            parser.pos += 9;
+            let text = parser.until("]]>")?;
+            stack
+                .last_mut()
+                .unwrap()
+                .children
+                .push(Node::Text(String::from(text)));
+        } else if rest.starts_with("<?") {
+            parser.until("?>")?;
+        } else if rest.starts_with("<!") {
+            parser.until(">")?;
+        } else if rest.starts_with("</") {
+            parser.pos += 2;
+            let name = parser.name();
+            parser.until(">")?;
+            // 关闭到同名的元素为止；找不到时忽略这个结束标签
+            if let Some(depth) = stack.iter().rposition(|e| e.name == name) {
+                if depth > 0 {
+                    while stack.len() > depth {
+                        let element = stack.pop().unwrap();
+                        stack
+                            .last_mut()
+                            .unwrap()
+                            .children
+                            .push(Node::Element(element));
+                    }
+                }
+            }
+        } else {
+            parser.pos += 1;
+            let (element, closed) = parser.start_tag()?;
+            if closed {
+                stack
+                    .last_mut()
+                    .unwrap()
+                    .children
+                    .push(Node::Element(element));
+            } else {
+                // 栈底的文档节点不算一层
+                if stack.len() > MAX_DEPTH {
+                    return Err(XmlError {
+                        offset: parser.start,
+                        message: String::from("nesting too deep"),
+                    });
+                }
+                stack.push(element);
+            }
+        }
+    }
+    // 文件结尾还没关闭的元素自动关闭
+    while stack.len() > 1 {
+        let element = stack.pop().unwrap();
+        stack
+            .last_mut()
+            .unwrap()
+            .children
+            .push(Node::Element(element));
+    }
+    let document = stack.pop().unwrap();
+    document
+        .children
+        .into_iter()
+        .find_map(|c| match c {
+            Node::Element(e) => Some(e),
+            Node::Text(_) => None,
+        })
+        .ok_or_else(|| XmlError {
+            offset: 0,
+            message: String::from("no root element"),
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_elements_attributes_and_text() {
+        let root = parse(
+            "<?xml version=\"1.0\"?>\n<!-- c --><a x='1' y=\"a &amp; b\"><b/>one &lt;<![CDATA[<two>]]><c>&#x4e2d;&#25991;</c></a>",
+        )
+        .unwrap();
+        assert_eq!("a", root.name);
+        assert_eq!(Some("a & b"), root.attr("y"));
+        assert!(root.child("b").is_some());
+        assert_eq!("one <<two>中文", root.text());
+        assert_eq!(Some(String::from("中文")), root.child_text("c"));
+        assert_eq!("&bogus; & x", unescape("&bogus; & x"));
+    }
+
+    #[test]
+    fn recovers_from_unbalanced_tags() {
+        // <p> 没有关闭，</x> 没有开始标签，</b> 也缺失
+        let root = parse("<a><p>text</x><b>bold</a>").unwrap();
+        assert_eq!("textbold", root.text());
+        assert_eq!(1, root.children_named("p").count());
+        assert!(parse("just text").is_err());
+        assert_eq!(
+            "at byte 4: missing `-->`",
+            parse("<a/><!-- oops").unwrap_err().to_string()
+        );
+    }
+
+    #[test]
+    fn rejects_deep_nesting() {
+        let root = parse(&"<a>".repeat(MAX_DEPTH)).unwrap();
+        assert_eq!("", root.text());
+        let err = parse(&"<a>".repeat(200_000)).unwrap_err();
+        assert_eq!("nesting too deep", err.message);
+        assert_eq!(3 * MAX_DEPTH, err.offset);
+    }
+}